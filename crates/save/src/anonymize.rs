@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Name, Savefile};
+
+
+/// Pseudonym steam ids count up from here, far above any account id Steam has handed out, so they
+/// can't point at a real account
+const PSEUDONYM_STEAM_ID_START: u32 = 0xFFFF_0000;
+
+
+#[derive(Debug, Default, Clone)]
+pub struct AnonymizeOptions {
+    /// Also reset the last played timestamp to the unix epoch
+    pub reset_last_played: bool,
+}
+
+
+#[derive(Debug, Default)]
+struct Pseudonyms {
+    names: HashMap<Vec<u8>, String>,
+    steam_ids: HashMap<u32, u32>,
+}
+
+impl Pseudonyms {
+    fn name(&mut self, name: &[u8]) -> String {
+        let next = self.names.len() + 1;
+        self.names
+            .entry(Self::name_key(name))
+            .or_insert_with(|| format!("Companion {}", next))
+            .clone()
+    }
+
+    /// A long name is cut short in the companion table but not in the symbol table, so names are
+    /// compared by the part that fits both, without a character cut in half at the end.
+    fn name_key(name: &[u8]) -> Vec<u8> {
+        let name = &name[..name.len().min(Name::<24>::MAX_LENGTH)];
+
+        match std::str::from_utf8(name) {
+            Err(err) if err.error_len().is_none() => name[..err.valid_up_to()].to_vec(),
+            _ => name.to_vec(),
+        }
+    }
+
+    fn steam_id(&mut self, steam_id: u32) -> u32 {
+        let next = PSEUDONYM_STEAM_ID_START + self.steam_ids.len() as u32;
+        *self.steam_ids.entry(steam_id).or_insert(next)
+    }
+}
+
+
+impl Savefile {
+    /// Replaces companion names and steam ids with pseudonyms.
+    ///
+    /// The same companion gets the same pseudonym in both the companion and the symbol table, while
+    /// symbols, counts and the layout of the file stay untouched.
+    pub fn anonymize(&mut self, options: &AnonymizeOptions) {
+        let mut pseudonyms = Pseudonyms::default();
+
+        for companion in self.companions.iter_mut() {
+            let name = pseudonyms.name(companion.name.as_bytes());
+            companion.name = Name::truncated(&name);
+            companion.steam_id = pseudonyms.steam_id(companion.steam_id);
+        }
+
+        for companion in self.companion_symbols.iter_mut() {
+            let name = pseudonyms.name(companion.name.as_bytes());
+            companion.name = Name::truncated(&name);
+        }
+
+        if options.reset_last_played {
+            self.last_played = DateTime::<Utc>::default();
+        }
+    }
+}
@@ -1,6 +1,21 @@
 use std::io::SeekFrom;
 
-use binrw::{BinRead, BinWrite, BinWriterExt, NullString};
+use binrw::{BinRead, BinWrite, BinWriterExt};
+
+use crate::{Error, Name, Result};
+
+
+pub const MAX_COMPANION_SYMBOL_ID: u32 = 21;
+
+/// Number of entries that fit into the companion symbols section
+pub const MAX_COMPANIONS: usize =
+    (CompanionSymbols::SECTION_SIZE / CompanionSymbols::ENTRY_SIZE) as usize;
+
+
+/// Formats the account id of a companion as a Steam ID3, e.g. `[U:1:12345]`
+pub fn steam_id_v3(steam_id: u32) -> String {
+    format!("[U:1:{}]", steam_id)
+}
 
 
 #[derive(Debug, Clone)]
@@ -11,9 +26,21 @@ impl Companions {
         self.0.iter()
     }
 
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<CompanionWithId> {
+        self.0.iter_mut()
+    }
+
     pub fn count(&self) -> usize {
         self.0.len()
     }
+
+    pub(crate) fn push(&mut self, companion: CompanionWithId) {
+        self.0.push(companion);
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Option<CompanionWithId> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
 }
 
 impl BinRead for Companions {
@@ -79,9 +106,21 @@ impl CompanionSymbols {
         self.0.iter()
     }
 
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<CompanionWithSymbol> {
+        self.0.iter_mut()
+    }
+
     pub fn count(&self) -> usize {
         self.0.len()
     }
+
+    pub(crate) fn push(&mut self, companion: CompanionWithSymbol) {
+        self.0.push(companion);
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Option<CompanionWithSymbol> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
 }
 
 impl BinRead for CompanionSymbols {
@@ -97,7 +136,7 @@ impl BinRead for CompanionSymbols {
     {
         let mut companions = Vec::new();
 
-        loop {
+        while companions.len() < MAX_COMPANIONS {
             let companion: CompanionWithSymbol = <_>::read_options(reader, endian, ())?;
 
             if companion.name.is_empty() {
@@ -138,17 +177,26 @@ impl BinWrite for CompanionSymbols {
 
 #[derive(Debug, Clone, BinRead, BinWrite)]
 pub struct CompanionWithId {
-    #[br(pad_size_to = 24, map = |raw: NullString| raw.to_string())]
-    #[bw(pad_size_to = 24, map = |s| NullString::from(s.as_ref()))]
-    pub name: String,
+    pub name: Name<24>,
 
     #[br(assert(steam_id != 0))]
     pub steam_id: u32,
 }
 
 impl CompanionWithId {
+    pub fn new(name: &str, steam_id: u32) -> Result<Self> {
+        if steam_id == 0 {
+            return Err(Error::InvalidSteamId);
+        }
+
+        Ok(Self {
+            name: Name::new(name)?,
+            steam_id,
+        })
+    }
+
     pub fn steam_id_v3(&self) -> String {
-        format!("[U:1:{}]", self.steam_id)
+        steam_id_v3(self.steam_id)
     }
 
     pub fn steam_url(&self) -> String {
@@ -161,13 +209,25 @@ impl CompanionWithId {
 
 #[derive(Debug, Clone, BinRead, BinWrite)]
 pub struct CompanionWithSymbol {
-    #[br(pad_size_to = 52, map = |raw: NullString| raw.to_string())]
-    #[bw(pad_size_to = 52, map = |s| NullString::from(s.as_ref()))]
-    pub name: String,
+    pub name: Name<52>,
 
     #[br(count = 4)]
     _unknown1: Vec<u8>,
 
-    #[br(assert((0..=21).contains(&symbol)))]
+    #[br(assert(symbol <= MAX_COMPANION_SYMBOL_ID))]
     pub symbol: u32,
 }
+
+impl CompanionWithSymbol {
+    pub fn new(name: &str, symbol: u32) -> Result<Self> {
+        if symbol > MAX_COMPANION_SYMBOL_ID {
+            return Err(Error::SymbolIdOutOfRange);
+        }
+
+        Ok(Self {
+            name: Name::new(name)?,
+            _unknown1: vec![0; 4],
+            symbol,
+        })
+    }
+}
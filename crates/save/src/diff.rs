@@ -0,0 +1,122 @@
+use core::fmt;
+
+use crate::level::NAMES as LEVEL_NAMES;
+use crate::Savefile;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+
+impl Savefile {
+    /// Lists every field as a pair of a field name and its displayed value.
+    ///
+    /// Glyphs and murals are named by level and 1-based position, e.g. `glyphs.Pink Desert:2`.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![
+            ("platform".to_string(), self.platform.to_string()),
+            ("journey_count".to_string(), self.journey_count.to_string()),
+            (
+                "total_companions_met".to_string(),
+                self.total_companions_met.to_string(),
+            ),
+            (
+                "total_collected_symbols".to_string(),
+                self.total_collected_symbols.to_string(),
+            ),
+            (
+                "collected_symbols".to_string(),
+                self.collected_symbols.to_string(),
+            ),
+            ("current_level".to_string(), self.current_level.to_string()),
+            (
+                "companions_met".to_string(),
+                self.companions_met.to_string(),
+            ),
+            ("scarf_length".to_string(), self.scarf_length.to_string()),
+            ("symbol".to_string(), self.symbol.as_ref().to_string()),
+            ("robe_color".to_string(), self.robe.color().to_string()),
+            ("robe_tier".to_string(), self.robe.tier().to_string()),
+            ("last_played".to_string(), self.last_played.to_string()),
+        ];
+
+        for (level, glyphs) in self.glyphs.all() {
+            for (index, collected) in glyphs.into_iter().enumerate() {
+                let field = format!("glyphs.{}:{}", LEVEL_NAMES[level], index + 1);
+                fields.push((field, on_off(collected)));
+            }
+        }
+
+        for (level, murals) in self.murals.all() {
+            for (index, found) in murals.into_iter().enumerate() {
+                let field = format!("murals.{}:{}", LEVEL_NAMES[level], index + 1);
+                fields.push((field, on_off(found)));
+            }
+        }
+
+        for (index, companion) in self.companions.iter().enumerate() {
+            let field = format!("companions[{}]", index);
+            let value = format!("{} ({})", companion.name, companion.steam_id);
+            fields.push((field, value));
+        }
+
+        for (index, companion) in self.companion_symbols.iter().enumerate() {
+            let field = format!("companion_symbols[{}]", index);
+            let value = format!("{} ({})", companion.name, companion.symbol);
+            fields.push((field, value));
+        }
+
+        fields
+    }
+
+    /// Compares all fields with another savefile and returns the ones that differ
+    pub fn diff(&self, other: &Savefile) -> Vec<Change> {
+        let old_fields = self.fields();
+        let new_fields = other.fields();
+
+        let mut changes = Vec::new();
+
+        for (field, old) in &old_fields {
+            let new = new_fields
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_else(|| "(none)".to_string());
+
+            if &new != old {
+                changes.push(Change {
+                    field: field.clone(),
+                    old: old.clone(),
+                    new,
+                });
+            }
+        }
+
+        for (field, new) in &new_fields {
+            if !old_fields.iter().any(|(name, _)| name == field) {
+                changes.push(Change {
+                    field: field.clone(),
+                    old: "(none)".to_string(),
+                    new: new.clone(),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+
+fn on_off(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+
+use crate::companion::{CompanionWithId, CompanionWithSymbol, MAX_COMPANIONS};
+use crate::robe::Color;
+use crate::{Change, Error, Result, Savefile};
+
+
+pub const MAX_COLLECTED_SYMBOLS: u32 = 21;
+
+/// Upper bound for the journey count, far beyond anything played, so typos are caught
+pub const MAX_JOURNEY_COUNT: u64 = 1_000_000;
+
+/// Upper bound for the total counters, well above meeting all companions and collecting all
+/// symbols on every journey
+pub const MAX_TOTAL_COUNT: u32 = 100 * MAX_JOURNEY_COUNT as u32;
+
+
+/// A single field assignment on a savefile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    JourneyCount(u64),
+    TotalCompanionsMet(u32),
+    TotalCollectedSymbols(u32),
+    CollectedSymbols(u32),
+    CurrentLevel(u64),
+    CompanionsMet(u32),
+    ScarfLength(u32),
+    Symbol(u32),
+    RobeColor(Color),
+    RobeTier(u32),
+    Glyph {
+        level: usize,
+        index: usize,
+        collected: bool,
+    },
+    Mural {
+        level: usize,
+        index: usize,
+        found: bool,
+    },
+    LastPlayed(DateTime<Utc>),
+    /// Appends a companion to both companion tables
+    AddCompanion {
+        name: String,
+        steam_id: u32,
+        symbol: u32,
+    },
+    /// Removes the companion at the given position from both companion tables
+    RemoveCompanion(usize),
+}
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EditMode {
+    /// Only the given field is changed
+    #[default]
+    Plain,
+
+    /// Fields that depend on the given field are changed along with it
+    Consistent,
+}
+
+
+impl Savefile {
+    /// Applies an edit and returns every field that changed because of it.
+    ///
+    /// In [`EditMode::Consistent`] the following dependencies are kept intact:
+    ///
+    /// - collecting glyphs raises `collected_symbols` to at least the number of collected glyphs
+    /// - `scarf_length` is at least as long as `collected_symbols`
+    /// - switching to the white robe collects all glyphs
+    /// - `companions_met` can not exceed the number of companion entries, larger values are
+    ///   rejected, and shrinks when a current companion is removed
+    /// - the total counters are never lower than the ones of the current journey
+    ///
+    /// Dependent fields are only ever raised to fit, except for lowering a total counter, which
+    /// lowers the counter of the current journey along with it.
+    pub fn apply(&mut self, edit: &Edit, mode: EditMode) -> Result<Vec<Change>> {
+        let original = self.clone();
+
+        if let Err(err) = self.apply_edit(edit, mode) {
+            *self = original;
+            return Err(err);
+        }
+
+        Ok(original.diff(self))
+    }
+
+    /// Like [`Savefile::apply`], but leaves `self` untouched and returns the edited copy
+    pub fn preview(&self, edit: &Edit, mode: EditMode) -> Result<(Savefile, Vec<Change>)> {
+        let mut savefile = self.clone();
+        let changes = savefile.apply(edit, mode)?;
+
+        Ok((savefile, changes))
+    }
+
+    fn apply_edit(&mut self, edit: &Edit, mode: EditMode) -> Result<()> {
+        let consistent = mode == EditMode::Consistent;
+
+        match *edit {
+            Edit::JourneyCount(value) => self.journey_count = value,
+            Edit::TotalCompanionsMet(value) => {
+                self.total_companions_met = value;
+                if consistent {
+                    self.companions_met = self.companions_met.min(value);
+                }
+            }
+            Edit::TotalCollectedSymbols(value) => {
+                self.total_collected_symbols = value;
+                if consistent {
+                    self.collected_symbols = self.collected_symbols.min(value);
+                }
+            }
+            Edit::CollectedSymbols(value) => {
+                if value > MAX_COLLECTED_SYMBOLS {
+                    return Err(Error::CollectedSymbolsOutOfRange);
+                }
+
+                self.collected_symbols = value;
+                if consistent {
+                    self.raise_symbol_counters(value)?;
+                }
+            }
+            Edit::CurrentLevel(id) => self.current_level.set_by_id(id)?,
+            Edit::CompanionsMet(value) => {
+                if consistent {
+                    let entries = self.companions.count() as u32;
+                    if value > entries {
+                        return Err(Error::CompanionsMetExceedsEntries(entries));
+                    }
+                    self.total_companions_met = self.total_companions_met.max(value);
+                }
+                self.companions_met = value;
+            }
+            Edit::ScarfLength(length) => self.scarf_length.set_length(length)?,
+            Edit::Symbol(id) => self.symbol.set_by_id(id)?,
+            Edit::RobeColor(color) => {
+                self.robe.set_color(color);
+                if consistent && color == Color::White {
+                    self.collect_all_glyphs()?;
+                }
+            }
+            Edit::RobeTier(tier) => self.robe.set_tier(tier).map_err(Error::RobeChange)?,
+            Edit::Glyph {
+                level,
+                index,
+                collected,
+            } => {
+                self.glyphs.set_collected(level, index, collected)?;
+                if consistent && collected {
+                    self.raise_symbol_counters(self.glyphs.collected() as u32)?;
+                }
+            }
+            Edit::Mural {
+                level,
+                index,
+                found,
+            } => self.murals.set_found(level, index, found)?,
+            Edit::LastPlayed(datetime) => self.last_played = datetime,
+            Edit::AddCompanion {
+                ref name,
+                steam_id,
+                symbol,
+            } => {
+                if self.companions.count() >= MAX_COMPANIONS {
+                    return Err(Error::CompanionLimitReached);
+                }
+
+                self.companions.push(CompanionWithId::new(name, steam_id)?);
+                self.companion_symbols
+                    .push(CompanionWithSymbol::new(name, symbol)?);
+            }
+            Edit::RemoveCompanion(index) => {
+                self.companions
+                    .remove(index)
+                    .ok_or(Error::CompanionIndexOutOfRange)?;
+                self.companion_symbols.remove(index);
+
+                if consistent && index < self.companions_met as usize {
+                    self.companions_met -= 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn collect_all_glyphs(&mut self) -> Result<()> {
+        let missing = self
+            .glyphs
+            .all()
+            .flat_map(|(level, glyphs)| {
+                glyphs
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, collected)| !collected)
+                    .map(move |(index, _)| (level, index))
+            })
+            .collect::<Vec<_>>();
+
+        for (level, index) in missing {
+            self.glyphs.set_collected(level, index, true)?;
+        }
+
+        self.raise_symbol_counters(self.glyphs.collected() as u32)
+    }
+
+    /// Raises the symbol counters and the scarf to at least `count`, never lowers them
+    pub(crate) fn raise_symbol_counters(&mut self, count: u32) -> Result<()> {
+        self.collected_symbols = self.collected_symbols.max(count);
+        self.total_collected_symbols = self.total_collected_symbols.max(count);
+
+        let scarf_length = *self.scarf_length.as_ref();
+        self.scarf_length.set_length(scarf_length.max(count))?;
+
+        Ok(())
+    }
+}
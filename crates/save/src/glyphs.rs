@@ -1,5 +1,7 @@
 use binrw::{BinRead, BinWrite};
 
+use crate::{Error, Result};
+
 
 #[derive(Debug, Clone, BinRead, BinWrite)]
 pub struct Glyphs(#[br(count = 6)] Vec<LevelGlyphs>);
@@ -7,10 +9,9 @@ pub struct Glyphs(#[br(count = 6)] Vec<LevelGlyphs>);
 impl Glyphs {
     const COUNT: [usize; 6] = [3, 3, 4, 3, 4, 4];
 
-    pub fn all<'a>(&'a self) -> impl Iterator<Item = (usize, Vec<bool>)> + 'a {
+    pub fn all(&self) -> impl Iterator<Item = (usize, Vec<bool>)> + '_ {
         self.0.iter().enumerate().map(|(level, glyphs)| {
             let glyphs = (0..Self::COUNT[level])
-                .into_iter()
                 .map(|glyph_idx| glyphs.has_collected(glyph_idx).unwrap())
                 .collect();
 
@@ -22,6 +23,12 @@ impl Glyphs {
         self.0.len()
     }
 
+    pub fn collected(&self) -> usize {
+        self.all()
+            .map(|(_, glyphs)| glyphs.into_iter().filter(|&g| g).count())
+            .sum()
+    }
+
     pub fn has_collected(&self, level: usize, index: usize) -> Option<bool> {
         if level >= self.0.len() {
             return None;
@@ -29,6 +36,16 @@ impl Glyphs {
 
         self.0[level].has_collected(index)
     }
+
+    pub fn set_collected(&mut self, level: usize, index: usize, collected: bool) -> Result<()> {
+        if level >= self.0.len() || index >= Self::COUNT[level] {
+            return Err(Error::GlyphOutOfRange);
+        }
+
+        self.0[level].set_collected(index, collected);
+
+        Ok(())
+    }
 }
 
 
@@ -48,4 +65,12 @@ impl LevelGlyphs {
 
         Some(((self.status_flags >> index) & 0x01) == 0x01)
     }
+
+    fn set_collected(&mut self, index: usize, collected: bool) {
+        if collected {
+            self.status_flags |= 0x01 << index;
+        } else {
+            self.status_flags &= !(0x01 << index);
+        }
+    }
 }
@@ -1,7 +1,13 @@
+mod anonymize;
 mod companion;
+mod diff;
+mod edit;
 mod glyphs;
 mod level;
+mod merge;
 mod murals;
+mod name;
+mod platform;
 mod robe;
 mod scarf;
 mod symbol;
@@ -9,7 +15,7 @@ mod test;
 
 
 use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 use binrw::{until_eof, BinRead, BinReaderExt, BinWriterExt};
@@ -19,11 +25,22 @@ use robe::Robe;
 use scarf::Scarf;
 use symbol::Symbol;
 
-use crate::companion::{CompanionSymbols, CompanionWithId, Companions};
+pub use crate::anonymize::AnonymizeOptions;
+pub use crate::companion::{
+    steam_id_v3, CompanionWithId, CompanionWithSymbol, MAX_COMPANIONS, MAX_COMPANION_SYMBOL_ID,
+};
+use crate::companion::{CompanionSymbols, Companions};
+pub use crate::diff::Change;
+pub use crate::edit::{Edit, EditMode, MAX_COLLECTED_SYMBOLS, MAX_JOURNEY_COUNT, MAX_TOTAL_COUNT};
 use crate::glyphs::Glyphs;
-pub use crate::level::NAMES as LEVEL_NAMES;
+pub use crate::level::{MAX_LEVEL_ID, NAMES as LEVEL_NAMES};
+pub use crate::merge::{CompanionPolicy, MergeEntry, MergeReport, MergeSource};
 use crate::murals::Murals;
-pub use crate::robe::Color as RobeColor;
+pub use crate::name::Name;
+pub use crate::platform::Platform;
+pub use crate::robe::{Color as RobeColor, MAX_TIER as MAX_ROBE_TIER, MIN_TIER as MIN_ROBE_TIER};
+pub use crate::scarf::MAX_LENGTH as MAX_SCARF_LENGTH;
+pub use crate::symbol::MAX_SYMBOL_ID;
 
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -55,21 +72,59 @@ pub enum Error {
     #[error("Symbol id is out of range")]
     SymbolIdOutOfRange,
 
+    #[error("Glyph is out of range")]
+    GlyphOutOfRange,
+
+    #[error("Mural is out of range")]
+    MuralOutOfRange,
+
+    #[error("Collected symbols can be at most {}", MAX_COLLECTED_SYMBOLS)]
+    CollectedSymbolsOutOfRange,
+
+    #[error("Steam id can not be 0")]
+    InvalidSteamId,
+
+    #[error("Savefile can hold at most {} companions", MAX_COMPANIONS)]
+    CompanionLimitReached,
+
+    #[error("Companions met can be at most {0}, the number of companion entries")]
+    CompanionsMetExceedsEntries(u32),
+
+    #[error("Companion index is out of range")]
+    CompanionIndexOutOfRange,
+
+    #[error("Invalid companion policy, expected left, right or newest")]
+    InvalidCompanionPolicy,
+
     #[error(transparent)]
     RobeChange(robe::Error),
 
     #[error("Failed to read file")]
     FileReadingFailed(io::Error),
+
+    #[error("Unknown savefile format (header {0:02X?})")]
+    UnknownFormat(Vec<u8>),
+
+    #[error("Invalid platform, expected pc or playstation")]
+    InvalidPlatform,
+
+    #[error("Name can be at most {0} bytes long")]
+    NameTooLong(usize),
+
+    #[error("Name can not contain null characters")]
+    NameContainsNull,
 }
 
 
 #[binrw::binrw]
 #[derive(Debug, Clone)]
-#[brw(little)]
 pub struct Savefile {
     #[brw(ignore)]
     pub path: PathBuf,
 
+    #[brw(ignore)]
+    pub platform: Platform,
+
     #[br(count = 8)]
     _unknown0: Vec<u8>,
 
@@ -86,7 +141,7 @@ pub struct Savefile {
 
     pub total_collected_symbols: u32,
 
-    #[br(assert(collected_symbols <= 21))]
+    #[br(assert(collected_symbols <= MAX_COLLECTED_SYMBOLS))]
     pub collected_symbols: u32,
 
     pub murals: Murals,
@@ -144,19 +199,65 @@ impl Savefile {
     where
         R: Read + BinReaderExt,
     {
-        Ok(reader.read_le().map_err(Error::DeserializationFailed)?)
+        let platform = Self::detect_platform(&mut reader)?;
+
+        let savefile = Self {
+            platform,
+            ..reader
+                .read_type(platform.endian())
+                .map_err(Error::DeserializationFailed)?
+        };
+
+        Ok(savefile)
+    }
+
+    pub fn detect_platform<R>(mut reader: R) -> Result<Platform>
+    where
+        R: Read + Seek,
+    {
+        let mut header = Vec::with_capacity(8);
+
+        let start = reader.stream_position().map_err(Error::FileReadingFailed)?;
+        (&mut reader)
+            .take(8)
+            .read_to_end(&mut header)
+            .map_err(Error::FileReadingFailed)?;
+        reader
+            .seek(SeekFrom::Start(start))
+            .map_err(Error::FileReadingFailed)?;
+
+        // too short to be a savefile of any platform
+        if header.len() < 8 {
+            return Err(Error::UnknownFormat(header));
+        }
+
+        Platform::detect(&header)
     }
 
     pub fn write<W>(&self, mut writer: W) -> Result<()>
     where
         W: Write + BinWriterExt,
     {
-        writer.write_le(self).map_err(Error::SerializationFailed)?;
+        writer
+            .write_type(self, self.platform.endian())
+            .map_err(Error::SerializationFailed)?;
 
         Ok(())
     }
 
-    pub fn current_companions<'a>(&'a self) -> impl Iterator<Item = &'a CompanionWithId> {
+    /// Copy of the savefile that is written with the byte order of `platform`.
+    ///
+    /// Only the header magic and the byte order of the known fields change, the unknown blocks are
+    /// copied as they are. Whether the game accepts the result on PlayStation is unverified, there
+    /// is no PlayStation savefile to compare against.
+    pub fn convert_to(&self, platform: Platform) -> Self {
+        let mut savefile = self.clone();
+        platform.rewrite_header(&mut savefile._unknown0);
+        savefile.platform = platform;
+        savefile
+    }
+
+    pub fn current_companions(&self) -> impl Iterator<Item = &'_ CompanionWithId> {
         self.companions
             .iter()
             .enumerate()
@@ -169,7 +270,7 @@ impl Savefile {
             })
     }
 
-    pub fn past_companions<'a>(&'a self) -> impl Iterator<Item = &'a CompanionWithId> {
+    pub fn past_companions(&self) -> impl Iterator<Item = &'_ CompanionWithId> {
         self.companions
             .iter()
             .enumerate()
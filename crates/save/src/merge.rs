@@ -0,0 +1,236 @@
+use core::fmt;
+use std::str::FromStr;
+
+use crate::{Error, Result, Savefile};
+
+
+/// Decides which savefile the companion lists are taken from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompanionPolicy {
+    Left,
+    Right,
+    #[default]
+    Newest,
+}
+
+impl FromStr for CompanionPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "newest" => Ok(Self::Newest),
+            _ => Err(Error::InvalidCompanionPolicy),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeSource {
+    Left,
+    Right,
+    /// Both savefiles had the same value
+    Both,
+    /// The value was combined from both savefiles
+    Union,
+}
+
+impl fmt::Display for MergeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+            Self::Both => write!(f, "both"),
+            Self::Union => write!(f, "union"),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct MergeEntry {
+    pub field: &'static str,
+    pub value: String,
+    pub source: MergeSource,
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub entries: Vec<MergeEntry>,
+}
+
+impl MergeReport {
+    fn push<V>(&mut self, field: &'static str, value: V, source: MergeSource)
+    where
+        V: fmt::Display,
+    {
+        self.entries.push(MergeEntry {
+            field,
+            value: value.to_string(),
+            source,
+        });
+    }
+
+    /// Records that `field` was raised to `value` to agree with other merged fields
+    fn raise<V>(&mut self, field: &str, value: V)
+    where
+        V: fmt::Display,
+    {
+        let value = value.to_string();
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.field == field && entry.value != value)
+        {
+            entry.value = value;
+            entry.source = MergeSource::Union;
+        }
+    }
+
+    pub fn source_of(&self, field: &str) -> Option<MergeSource> {
+        self.entries
+            .iter()
+            .find(|entry| entry.field == field)
+            .map(|entry| entry.source)
+    }
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}: {} ({})", entry.field, entry.value, entry.source)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+impl Savefile {
+    /// Combines the progress of two savefiles.
+    ///
+    /// Glyphs and murals are unioned, counters take the maximum and the better robe is kept.
+    /// Counters that depend on the glyphs are raised to cover the unioned glyphs, like consistent
+    /// edits do. Everything else is taken from `self`.
+    pub fn merge(&self, other: &Savefile, policy: CompanionPolicy) -> (Savefile, MergeReport) {
+        let mut merged = self.clone();
+        let mut report = MergeReport::default();
+
+        macro_rules! take_max {
+            ($field:ident) => {{
+                let (value, source) = pick_max(&self.$field, &other.$field);
+                merged.$field = value.clone();
+                report.push(stringify!($field), value, source);
+            }};
+        }
+
+        take_max!(journey_count);
+        take_max!(total_companions_met);
+        take_max!(total_collected_symbols);
+        take_max!(collected_symbols);
+        take_max!(last_played);
+
+        let (robe, source) = pick_max(&self.robe, &other.robe);
+        merged.robe = robe.clone();
+        report.push(
+            "robe",
+            format!("{} tier {}", robe.color(), robe.tier()),
+            source,
+        );
+
+        let (scarf_length, source) =
+            pick_max(self.scarf_length.as_ref(), other.scarf_length.as_ref());
+        merged.scarf_length = match source {
+            MergeSource::Right => other.scarf_length.clone(),
+            _ => self.scarf_length.clone(),
+        };
+        report.push("scarf_length", scarf_length, source);
+
+        let mut glyph_sources = Vec::new();
+        for ((level, left), (_, right)) in self.glyphs.all().zip(other.glyphs.all()) {
+            for (index, (&left, &right)) in left.iter().zip(right.iter()).enumerate() {
+                glyph_sources.push(bool_source(left, right));
+                // the indices come from iterating the glyphs themselves
+                merged
+                    .glyphs
+                    .set_collected(level, index, left || right)
+                    .unwrap();
+            }
+        }
+        let collected = merged.glyphs.collected();
+        report.push("glyphs", collected, combined_source(glyph_sources));
+
+        // there are fewer glyphs than the longest scarf
+        merged.raise_symbol_counters(collected as u32).unwrap();
+        report.raise("collected_symbols", merged.collected_symbols);
+        report.raise("total_collected_symbols", merged.total_collected_symbols);
+        report.raise("scarf_length", merged.scarf_length.as_ref());
+
+        let mut mural_sources = Vec::new();
+        for ((level, left), (_, right)) in self.murals.all().zip(other.murals.all()) {
+            for (index, (&left, &right)) in left.iter().zip(right.iter()).enumerate() {
+                mural_sources.push(bool_source(left, right));
+                merged
+                    .murals
+                    .set_found(level, index, left || right)
+                    .unwrap();
+            }
+        }
+        let found = merged.murals.all().flat_map(|(_, m)| m).filter(|&m| m);
+        report.push("murals", found.count(), combined_source(mural_sources));
+
+        let companion_source = match policy {
+            CompanionPolicy::Left => MergeSource::Left,
+            CompanionPolicy::Right => MergeSource::Right,
+            CompanionPolicy::Newest if other.last_played > self.last_played => MergeSource::Right,
+            CompanionPolicy::Newest => MergeSource::Left,
+        };
+        let companions_from = match companion_source {
+            MergeSource::Right => other,
+            _ => self,
+        };
+        merged.companions = companions_from.companions.clone();
+        merged.companion_symbols = companions_from.companion_symbols.clone();
+        merged.companions_met = companions_from.companions_met;
+        report.push("companions", merged.companions.count(), companion_source);
+
+        (merged, report)
+    }
+}
+
+
+fn pick_max<'a, T>(left: &'a T, right: &'a T) -> (&'a T, MergeSource)
+where
+    T: PartialOrd,
+{
+    if left == right {
+        (left, MergeSource::Both)
+    } else if right > left {
+        (right, MergeSource::Right)
+    } else {
+        (left, MergeSource::Left)
+    }
+}
+
+fn bool_source(left: bool, right: bool) -> MergeSource {
+    match (left, right) {
+        (true, false) => MergeSource::Left,
+        (false, true) => MergeSource::Right,
+        _ => MergeSource::Both,
+    }
+}
+
+fn combined_source(sources: Vec<MergeSource>) -> MergeSource {
+    let has_left = sources.contains(&MergeSource::Left);
+    let has_right = sources.contains(&MergeSource::Right);
+
+    match (has_left, has_right) {
+        (true, true) => MergeSource::Union,
+        (true, false) => MergeSource::Left,
+        (false, true) => MergeSource::Right,
+        (false, false) => MergeSource::Both,
+    }
+}
@@ -1,5 +1,7 @@
 use binrw::{BinRead, BinWrite};
 
+use crate::{Error, Result};
+
 
 #[derive(Debug, Clone, Copy, BinRead, BinWrite)]
 pub struct Murals {
@@ -18,15 +20,33 @@ impl Murals {
             return None;
         }
 
-        let pos = Self::COUNT[0..level_index].iter().sum::<usize>();
-        let mask = 0x01 << (pos + index);
+        let mask = Self::mask(level_index, index);
         Some((self.status_flags & mask) == mask)
     }
 
-    pub fn all<'a>(&'a self) -> impl Iterator<Item = (usize, Vec<bool>)> + 'a {
+    pub fn set_found(&mut self, level_index: usize, index: usize, found: bool) -> Result<()> {
+        if level_index > 6 || index >= Self::COUNT[level_index] {
+            return Err(Error::MuralOutOfRange);
+        }
+
+        let mask = Self::mask(level_index, index);
+        if found {
+            self.status_flags |= mask;
+        } else {
+            self.status_flags &= !mask;
+        }
+
+        Ok(())
+    }
+
+    fn mask(level_index: usize, index: usize) -> u16 {
+        let pos = Self::COUNT[0..level_index].iter().sum::<usize>();
+        0x01 << (pos + index)
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = (usize, Vec<bool>)> + '_ {
         Self::COUNT.iter().enumerate().map(|(level, murals)| {
             let murals = (0..*murals)
-                .into_iter()
                 .map(|mural| self.has_found(level, mural).unwrap())
                 .collect::<Vec<_>>();
 
@@ -0,0 +1,231 @@
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use binrw::{BinRead, BinWrite};
+
+use crate::{Error, Result};
+
+
+/// A null terminated name stored in a fixed size field of `SIZE` bytes.
+///
+/// The raw bytes are kept as read, so names that are not valid UTF-8 are written back unchanged.
+///
+/// For the same reason reading accepts a field without a terminator, filled with all `SIZE` bytes.
+/// Such a name is longer than [`Name::MAX_LENGTH`], which only limits names created with
+/// [`Name::new`] or [`Name::truncated`], but rejecting it would make the savefile unreadable.
+#[derive(Clone, Eq)]
+pub struct Name<const SIZE: usize> {
+    raw: Vec<u8>,
+}
+
+impl<const SIZE: usize> Name<SIZE> {
+    /// Maximum length in bytes, leaving room for the null terminator
+    pub const MAX_LENGTH: usize = SIZE - 1;
+
+    pub fn new(name: &str) -> Result<Self> {
+        if name.len() > Self::MAX_LENGTH {
+            return Err(Error::NameTooLong(Self::MAX_LENGTH));
+        }
+
+        if name.contains('\0') {
+            return Err(Error::NameContainsNull);
+        }
+
+        Ok(Self {
+            raw: name.as_bytes().to_vec(),
+        })
+    }
+
+    /// Creates a name that is cut off at the last character boundary that fits the field.
+    pub fn truncated(name: &str) -> Self {
+        let name = name.split('\0').next().unwrap_or_default();
+
+        let end = name
+            .char_indices()
+            .map(|(idx, ch)| idx + ch.len_utf8())
+            .take_while(|&end| end <= Self::MAX_LENGTH)
+            .last()
+            .unwrap_or(0);
+
+        Self {
+            raw: name.as_bytes()[..end].to_vec(),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.raw).ok()
+    }
+
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.raw)
+    }
+
+    pub fn is_lossy(&self) -> bool {
+        self.as_str().is_none()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+impl<const SIZE: usize> BinRead for Name<SIZE> {
+    type Args<'a> = ();
+
+    fn read_options<R>(
+        reader: &mut R,
+        _endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let mut field = [0u8; SIZE];
+        reader.read_exact(&mut field)?;
+
+        // an unterminated field is kept whole, see the type docs
+        let end = field.iter().position(|&b| b == 0).unwrap_or(SIZE);
+
+        Ok(Self {
+            raw: field[..end].to_vec(),
+        })
+    }
+}
+
+impl<const SIZE: usize> BinWrite for Name<SIZE> {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        _endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        let mut field = [0u8; SIZE];
+        let len = self.raw.len().min(SIZE);
+        field[..len].copy_from_slice(&self.raw[..len]);
+
+        writer.write_all(&field)?;
+
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> FromStr for Name<SIZE> {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl<const SIZE: usize> fmt::Display for Name<SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+impl<const SIZE: usize> fmt::Debug for Name<SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
+
+impl<const A: usize, const B: usize> PartialEq<Name<B>> for Name<A> {
+    fn eq(&self, other: &Name<B>) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<const SIZE: usize> Hash for Name<SIZE> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<const SIZE: usize> PartialEq<str> for Name<SIZE> {
+    fn eq(&self, other: &str) -> bool {
+        self.raw == other.as_bytes()
+    }
+}
+
+impl<const SIZE: usize> PartialEq<&str> for Name<SIZE> {
+    fn eq(&self, other: &&str) -> bool {
+        self.raw == other.as_bytes()
+    }
+}
+
+impl<const SIZE: usize> PartialEq<String> for Name<SIZE> {
+    fn eq(&self, other: &String) -> bool {
+        self.raw == other.as_bytes()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use binrw::{BinReaderExt, BinWriterExt};
+
+    use super::*;
+
+    #[test]
+    fn enforce_byte_budget() {
+        assert!(Name::<8>::new("1234567").is_ok());
+        assert!(matches!(
+            Name::<8>::new("12345678"),
+            Err(Error::NameTooLong(7))
+        ));
+
+        // 3 characters, but 9 bytes
+        assert!(matches!(
+            Name::<8>::new("旅人達"),
+            Err(Error::NameTooLong(7))
+        ));
+    }
+
+    #[test]
+    fn truncate_on_char_boundary() {
+        assert_eq!(Name::<8>::truncated("12345678"), "1234567");
+        assert_eq!(Name::<8>::truncated("旅人達"), "旅人");
+        assert_eq!(Name::<8>::truncated("ab🦊🦊"), "ab🦊");
+        assert_eq!(Name::<8>::truncated("a\0b"), "a");
+    }
+
+    #[test]
+    fn keep_raw_bytes() {
+        let field = [0x41, 0xFF, 0xFE, 0x42, 0x00, 0x00, 0x00, 0x00];
+
+        let name: Name<8> = Cursor::new(field).read_le().unwrap();
+        assert!(name.is_lossy());
+        assert_eq!(name.to_string(), "A\u{FFFD}\u{FFFD}B");
+
+        let mut written = Cursor::new(Vec::new());
+        written.write_le(&name).unwrap();
+        assert_eq!(written.into_inner(), field);
+    }
+
+    #[test]
+    fn unterminated_field() {
+        let field = *b"12345678";
+
+        let name: Name<8> = Cursor::new(field).read_le().unwrap();
+        assert_eq!(name, "12345678");
+        assert!(name.len() > Name::<8>::MAX_LENGTH);
+
+        let mut written = Cursor::new(Vec::new());
+        written.write_le(&name).unwrap();
+        assert_eq!(written.into_inner(), field);
+    }
+}
@@ -0,0 +1,73 @@
+use core::fmt;
+use std::str::FromStr;
+
+use binrw::Endian;
+
+use crate::{Error, Result};
+
+
+const HEADER_MAGIC: u32 = 0x8000_0016;
+const HEADER_MAGIC_SIZE: usize = 4;
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    #[default]
+    Pc,
+    PlayStation,
+}
+
+impl Platform {
+    pub const ALL: [Self; 2] = [Self::Pc, Self::PlayStation];
+
+    pub fn detect(header: &[u8]) -> Result<Self> {
+        let magic: [u8; HEADER_MAGIC_SIZE] = header
+            .get(..HEADER_MAGIC_SIZE)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::UnknownFormat(header.to_vec()))?;
+
+        Self::ALL
+            .into_iter()
+            .find(|platform| platform.header_magic() == magic)
+            .ok_or(Error::UnknownFormat(magic.to_vec()))
+    }
+
+    pub fn endian(&self) -> Endian {
+        match self {
+            Self::Pc => Endian::Little,
+            Self::PlayStation => Endian::Big,
+        }
+    }
+
+    pub(crate) fn header_magic(&self) -> [u8; HEADER_MAGIC_SIZE] {
+        match self.endian() {
+            Endian::Little => HEADER_MAGIC.to_le_bytes(),
+            Endian::Big => HEADER_MAGIC.to_be_bytes(),
+        }
+    }
+
+    pub(crate) fn rewrite_header(&self, header: &mut [u8]) {
+        header[..HEADER_MAGIC_SIZE].copy_from_slice(&self.header_magic());
+    }
+}
+
+impl FromStr for Platform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PC" | "pc" => Ok(Self::Pc),
+            "PlayStation" | "playstation" => Ok(Self::PlayStation),
+            _ => Err(Error::InvalidPlatform),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pc => write!(f, "PC"),
+            Self::PlayStation => write!(f, "PlayStation"),
+        }
+    }
+}
@@ -6,8 +6,8 @@ use binrw::{BinRead, BinWrite};
 use crate::Result;
 
 
-const MIN_TIER: u32 = 1;
-const MAX_TIER: u32 = 4;
+pub const MIN_TIER: u32 = 1;
+pub const MAX_TIER: u32 = 4;
 const MAX_RED_TIER_ID: u32 = 3;
 
 
@@ -24,7 +24,8 @@ pub enum Error {
 }
 
 
-#[derive(Debug, Clone, BinRead, BinWrite)]
+/// Robes are ordered by progression, any white robe ranks above all red robes
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, BinRead, BinWrite)]
 pub struct Robe {
     value: u32,
 }
@@ -64,7 +65,7 @@ impl Robe {
     }
 
     pub fn set_tier(&mut self, tier: u32) -> Result<(), Error> {
-        if tier < MIN_TIER || tier > MAX_TIER {
+        if !(MIN_TIER..=MAX_TIER).contains(&tier) {
             return Err(Error::TierOutOfRange);
         }
 
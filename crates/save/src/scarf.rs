@@ -5,7 +5,7 @@ use binrw::{BinRead, BinWrite};
 use crate::{Error, Result};
 
 
-const MAX_LENGTH: u32 = 30;
+pub const MAX_LENGTH: u32 = 30;
 
 
 #[derive(Debug, Clone, BinRead, BinWrite)]
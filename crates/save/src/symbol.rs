@@ -6,7 +6,7 @@ use substring::Substring;
 use crate::{Error, Result};
 
 
-const MAX_SYMBOL_ID: u32 = 20;
+pub const MAX_SYMBOL_ID: u32 = 20;
 const SYMBOL_PARTS: &str = include_str!("symbol_parts.txt");
 const SYMBOL_PART_WIDTH: usize = 6;
 const SYMBOL_PART_HEIGTH: usize = 3;
@@ -14,7 +14,7 @@ const SYMBOL_PART_HEIGTH: usize = 3;
 
 #[derive(Debug, Clone, Copy, BinRead, BinWrite)]
 pub struct Symbol {
-    #[br(assert(id < MAX_SYMBOL_ID))]
+    #[br(assert(id <= MAX_SYMBOL_ID))]
     id: u32,
 }
 
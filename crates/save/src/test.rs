@@ -30,7 +30,7 @@ fn general_info() {
 fn last_played() {
     let savefile = savefile();
 
-    let expected = NaiveDate::from_ymd_opt(2023, 07, 28).unwrap();
+    let expected = NaiveDate::from_ymd_opt(2023, 7, 28).unwrap();
     let expected = expected.and_hms_milli_opt(14, 17, 45, 893).unwrap();
 
     assert_eq!(savefile.last_played.naive_utc(), expected);
@@ -103,7 +103,7 @@ fn glyph_status() {
     ];
 
     for (level_idx, level_found) in FOUND.into_iter().enumerate() {
-        for (glyph_idx, has_found) in level_found.into_iter().enumerate() {
+        for (glyph_idx, has_found) in level_found.iter().enumerate() {
             assert_eq!(
                 savefile.glyphs.has_collected(level_idx, glyph_idx),
                 Some(*has_found),
@@ -133,7 +133,7 @@ fn murals() {
     ];
 
     for (level_idx, level_found) in FOUND.into_iter().enumerate() {
-        for (mural_idx, has_found) in level_found.into_iter().enumerate() {
+        for (mural_idx, has_found) in level_found.iter().enumerate() {
             assert_eq!(
                 savefile.murals.has_found(level_idx, mural_idx),
                 Some(*has_found),
@@ -148,8 +148,281 @@ fn murals() {
 }
 
 
+#[test]
+fn platform_detection() {
+    let savefile = Savefile::from_reader(Cursor::new(TEST_FILE)).unwrap();
+    assert_eq!(savefile.platform, Platform::Pc);
+
+    let mut unknown = TEST_FILE.to_vec();
+    unknown[..4].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+    let result = Savefile::from_reader(Cursor::new(unknown));
+    assert!(matches!(result, Err(Error::UnknownFormat(_))));
+
+    let result = Savefile::from_reader(Cursor::new(&TEST_FILE[..5]));
+    assert!(matches!(result, Err(Error::UnknownFormat(header)) if header.len() == 5));
+}
+
+
+#[test]
+fn platform_conversion() {
+    let savefile = Savefile::from_reader(Cursor::new(TEST_FILE)).unwrap();
+
+    let mut converted = Cursor::new(Vec::new());
+    savefile
+        .convert_to(Platform::PlayStation)
+        .write(&mut converted)
+        .unwrap();
+    assert_ne!(converted.get_ref().as_slice(), TEST_FILE);
+
+    converted.set_position(0);
+    let playstation = Savefile::from_reader(&mut converted).unwrap();
+    assert_eq!(playstation.platform, Platform::PlayStation);
+    assert_eq!(playstation.journey_count, savefile.journey_count);
+    assert_eq!(playstation.last_played, savefile.last_played);
+    assert_eq!(playstation.companions.count(), savefile.companions.count());
+
+    let mut restored = Cursor::new(Vec::new());
+    playstation
+        .convert_to(Platform::Pc)
+        .write(&mut restored)
+        .unwrap();
+    assert_eq!(restored.get_ref().as_slice(), TEST_FILE);
+}
+
+
+#[test]
+fn anonymize() {
+    let original = savefile();
+    let mut savefile = original.clone();
+
+    savefile.anonymize(&AnonymizeOptions {
+        reset_last_played: true,
+    });
+
+    for (a, b) in savefile
+        .companions
+        .iter()
+        .zip(savefile.companion_symbols.iter())
+    {
+        assert_eq!(a.name, b.name);
+        assert!(a.name.to_string().starts_with("Companion "));
+    }
+
+    for (anonymized, original) in savefile.companions.iter().zip(original.companions.iter()) {
+        assert_ne!(anonymized.steam_id, original.steam_id);
+        assert_ne!(anonymized.steam_id, 0);
+    }
+
+    for (anonymized, original) in savefile
+        .companion_symbols
+        .iter()
+        .zip(original.companion_symbols.iter())
+    {
+        assert_eq!(anonymized.symbol, original.symbol);
+    }
+
+    assert_eq!(savefile.companions_met, original.companions_met);
+    assert_eq!(savefile.last_played.timestamp(), 0);
+
+    let mut written = Cursor::new(Vec::new());
+    savefile.write(&mut written).unwrap();
+    assert_eq!(written.get_ref().len(), TEST_FILE.len());
+
+    written.set_position(0);
+    let reread = Savefile::from_reader(written).unwrap();
+    assert_eq!(reread.companions.count(), original.companions.count());
+    assert_eq!(
+        reread.companion_symbols.count(),
+        original.companion_symbols.count()
+    );
+}
+
+
+#[test]
+fn anonymize_long_name() {
+    const LONG_NAME: &str = "A rather long companion name";
+
+    let mut savefile = savefile();
+    savefile.companions.iter_mut().next().unwrap().name = Name::truncated(LONG_NAME);
+    savefile.companion_symbols.iter_mut().next().unwrap().name = Name::new(LONG_NAME).unwrap();
+
+    savefile.anonymize(&AnonymizeOptions::default());
+
+    let companion = savefile.companions.iter().next().unwrap();
+    let symbol = savefile.companion_symbols.iter().next().unwrap();
+    assert_eq!(companion.name, "Companion 1");
+    assert_eq!(symbol.name, "Companion 1");
+    assert!(companion.steam_id >= 0xFFFF_0000);
+}
+
+
+#[test]
+fn merge() {
+    let mut left = savefile();
+    let mut right = savefile();
+
+    // both agree with their own 13 glyphs, but not with the 14 of the merged savefile
+    left.collected_symbols = 13;
+    right.collected_symbols = 13;
+
+    right.journey_count = 30;
+    right.robe.set_color(RobeColor::White);
+    right.glyphs.set_collected(0, 1, true).unwrap();
+    right.glyphs.set_collected(0, 0, false).unwrap();
+    right.murals.set_found(0, 0, true).unwrap();
+    right.last_played = left.last_played - chrono::Duration::days(1);
+
+    let (merged, report) = left.merge(&right, CompanionPolicy::Newest);
+
+    assert_eq!(merged.journey_count, 30);
+    assert_eq!(report.source_of("journey_count"), Some(MergeSource::Right));
+    assert_eq!(merged.total_companions_met, left.total_companions_met);
+    assert_eq!(
+        report.source_of("total_companions_met"),
+        Some(MergeSource::Both)
+    );
+
+    assert_eq!(merged.robe.color(), RobeColor::White);
+    assert_eq!(report.source_of("robe"), Some(MergeSource::Right));
+
+    assert_eq!(merged.glyphs.has_collected(0, 0), Some(true));
+    assert_eq!(merged.glyphs.has_collected(0, 1), Some(true));
+    assert_eq!(report.source_of("glyphs"), Some(MergeSource::Union));
+    assert_eq!(merged.collected_symbols, 14);
+    assert_eq!(
+        report.source_of("collected_symbols"),
+        Some(MergeSource::Union)
+    );
+    assert_eq!(report.source_of("scarf_length"), Some(MergeSource::Both));
+    assert_eq!(merged.murals.has_found(0, 0), Some(true));
+    assert_eq!(report.source_of("murals"), Some(MergeSource::Right));
+
+    assert_eq!(merged.last_played, left.last_played);
+    assert_eq!(report.source_of("companions"), Some(MergeSource::Left));
+
+    let (_, report) = left.merge(&right, CompanionPolicy::Right);
+    assert_eq!(report.source_of("companions"), Some(MergeSource::Right));
+}
+
+
+#[test]
+fn plain_edit() {
+    let mut savefile = savefile();
+
+    let changes = savefile
+        .apply(&Edit::RobeColor(RobeColor::White), EditMode::Plain)
+        .unwrap();
+
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "robe_color");
+    assert_eq!(savefile.glyphs.collected(), 13);
+}
+
+
+#[test]
+fn consistent_edit() {
+    let original = savefile();
+
+    let (savefile, changes) = original
+        .preview(&Edit::RobeColor(RobeColor::White), EditMode::Consistent)
+        .unwrap();
+
+    assert_eq!(original.robe.color(), RobeColor::Red);
+    assert_eq!(savefile.robe.color(), RobeColor::White);
+    assert_eq!(savefile.glyphs.collected(), 21);
+    assert_eq!(savefile.collected_symbols, 21);
+    assert_eq!(savefile.scarf_length.as_ref(), &27);
+    assert!(changes.iter().any(|c| c.field == "glyphs.Chapter Select:2"));
+    assert!(!changes.iter().any(|c| c.field == "scarf_length"));
+
+    let (savefile, _) = original
+        .preview(&Edit::CollectedSymbols(10), EditMode::Consistent)
+        .unwrap();
+    assert_eq!(savefile.collected_symbols, 10);
+    assert_eq!(savefile.glyphs.collected(), 13);
+    assert_eq!(savefile.scarf_length.as_ref(), &27);
+    assert_eq!(savefile.total_collected_symbols, 107);
+
+    let (savefile, _) = original
+        .preview(&Edit::TotalCollectedSymbols(15), EditMode::Consistent)
+        .unwrap();
+    assert_eq!(savefile.collected_symbols, 15);
+
+    let result = original.preview(&Edit::CompanionsMet(20), EditMode::Consistent);
+    assert!(matches!(result, Err(Error::CompanionsMetExceedsEntries(8))));
+
+    let (savefile, _) = original
+        .preview(&Edit::CompanionsMet(7), EditMode::Consistent)
+        .unwrap();
+    assert_eq!(savefile.companions_met, 7);
+
+    let (savefile, _) = original
+        .preview(&Edit::CompanionsMet(20), EditMode::Plain)
+        .unwrap();
+    assert_eq!(savefile.companions_met, 20);
+}
+
+
+#[test]
+fn failed_edit_is_rolled_back() {
+    let mut savefile = savefile();
+
+    let result = savefile.apply(&Edit::CollectedSymbols(22), EditMode::Consistent);
+
+    assert!(matches!(result, Err(Error::CollectedSymbolsOutOfRange)));
+    assert_eq!(savefile.collected_symbols, 21);
+}
+
+
+#[test]
+fn companion_edits() {
+    let mut savefile = savefile();
+
+    let add = Edit::AddCompanion {
+        name: "Newcomer".to_string(),
+        steam_id: 1234,
+        symbol: 3,
+    };
+    savefile.apply(&add, EditMode::Plain).unwrap();
+
+    assert_eq!(savefile.companions.count(), 9);
+    assert_eq!(savefile.past_companions().last().unwrap().name, "Newcomer");
+    assert_eq!(savefile.companion_symbols.iter().last().unwrap().symbol, 3);
+
+    savefile
+        .apply(&Edit::RemoveCompanion(0), EditMode::Consistent)
+        .unwrap();
+
+    assert_eq!(savefile.companions.count(), 8);
+    assert_eq!(savefile.companion_symbols.count(), 8);
+    assert_eq!(savefile.companions_met, 5);
+    assert_eq!(
+        savefile.current_companions().next().unwrap().name,
+        "Rythulian"
+    );
+
+    let mut written = Cursor::new(Vec::new());
+    savefile.write(&mut written).unwrap();
+    written.set_position(0);
+    let reread = Savefile::from_reader(written).unwrap();
+    assert_eq!(reread.companions.count(), 8);
+    assert_eq!(reread.past_companions().last().unwrap().name, "Newcomer");
+
+    let invalid = Edit::AddCompanion {
+        name: "Nobody".to_string(),
+        steam_id: 0,
+        symbol: 3,
+    };
+    let result = savefile.apply(&invalid, EditMode::Plain);
+    assert!(matches!(result, Err(Error::InvalidSteamId)));
+    assert_eq!(savefile.companions.count(), 8);
+}
+
+
+const TEST_FILE: &[u8] = include_bytes!("../test.bin");
+
+
 fn savefile() -> Savefile {
-    const TEST_FILE: &[u8] = include_bytes!("../test.bin");
     let mut savefile = Cursor::new(TEST_FILE);
     savefile.read_le().expect("parsing failed")
 }
@@ -0,0 +1,47 @@
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser as ArgParser;
+use jrny_save::{AnonymizeOptions, Savefile};
+
+use crate::edit::{confirm_overwrite, differs_from};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    in_path: PathBuf,
+    out_path: PathBuf,
+
+    /// Also reset the last played timestamp
+    #[arg(long)]
+    reset_last_played: bool,
+
+    /// Overwrites an existing output file without asking
+    #[arg(long, short)]
+    yes: bool,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let in_file = File::open(&sub_args.in_path)?;
+
+    let mut savefile = Savefile::from_reader(in_file)?;
+
+    savefile.anonymize(&AnonymizeOptions {
+        reset_last_played: sub_args.reset_last_played,
+    });
+
+    let mut data = Vec::new();
+    savefile.write(Cursor::new(&mut data))?;
+
+    if !sub_args.yes && differs_from(&sub_args.out_path, &data) {
+        confirm_overwrite(&sub_args.out_path)?;
+    }
+
+    fs::write(&sub_args.out_path, data)?;
+
+    Ok(())
+}
@@ -0,0 +1,670 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::edit::{confirm_overwrite, write_atomic, write_data};
+use crate::info::{SaveInfo, Section};
+use crate::show::render_table;
+use crate::{AppArgs, DIRS};
+
+
+const INDEX_FILE_NAME: &str = "snapshots.jsonl";
+
+/// Length of the shortened hashes shown to users
+pub(crate) const SHORT_ID_LEN: usize = 12;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    #[command(subcommand)]
+    command: BackupCommand,
+}
+
+
+#[derive(Debug, Clone, clap::Subcommand)]
+enum BackupCommand {
+    /// List stored versions, newest first
+    List(ListArgs),
+
+    /// Print a stored version
+    Show(ShowArgs),
+
+    /// Write a stored version back, the replaced file is stored as well
+    Restore(RestoreArgs),
+
+    /// Remove versions not covered by any of the given retention policies
+    Prune(PruneArgs),
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct ListArgs {
+    /// Only list versions of this savefile
+    path: Option<PathBuf>,
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct ShowArgs {
+    /// Hash of the version, a unique prefix is enough
+    id: String,
+
+    /// Print the fields that differ from the current file instead of the whole version
+    #[arg(long, short)]
+    diff: bool,
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct RestoreArgs {
+    /// Hash of the version, a unique prefix is enough
+    id: String,
+
+    /// Restores to another path instead of the original one
+    #[arg(long)]
+    to: Option<PathBuf>,
+
+    /// Restores without asking
+    #[arg(long, short)]
+    yes: bool,
+
+    /// Updates Steam Cloud's remotecache.vdf next to the restored savefile
+    #[arg(long)]
+    steam_cloud: bool,
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct PruneArgs {
+    /// Keeps the given number of most recent versions of each savefile
+    #[arg(long)]
+    keep_last: Option<usize>,
+
+    /// Keeps the most recent version of each of the given number of days
+    #[arg(long)]
+    keep_daily: Option<usize>,
+
+    /// Keeps versions younger than the given age in m, h, d or w, e.g. 12h, 30d or 8w
+    #[arg(long, value_parser = parse_age)]
+    keep_within: Option<Duration>,
+
+    /// Prints what would be removed without removing anything
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+}
+
+
+/// Why a version was stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reason {
+    Edit,
+    Save,
+    Reload,
+    Restore,
+    Slot,
+}
+
+
+/// A stored version of a savefile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub hash: String,
+    pub path: PathBuf,
+    pub created: DateTime<Utc>,
+    pub reason: Reason,
+    pub size: u64,
+}
+
+
+/// Which versions to keep when pruning, a version is kept if any policy keeps it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Retention {
+    pub keep_last: Option<usize>,
+    pub keep_daily: Option<usize>,
+    pub keep_within: Option<Duration>,
+}
+
+
+/// Content-addressed store of savefile versions.
+///
+/// Contents are stored once per hash in `objects`, while `snapshots.jsonl` records which file
+/// had which content when.
+#[derive(Debug, Clone)]
+pub(crate) struct BackupStore {
+    dir: PathBuf,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let store = BackupStore::open()?;
+
+    match &sub_args.command {
+        BackupCommand::List(args) => list(&store, args),
+        BackupCommand::Show(args) => show(&store, args),
+        BackupCommand::Restore(args) => restore(&store, args),
+        BackupCommand::Prune(args) => prune(&store, args),
+    }
+}
+
+
+fn list(store: &BackupStore, args: &ListArgs) -> Result<()> {
+    let snapshots = match &args.path {
+        Some(path) => store.history(path)?,
+        None => {
+            let mut snapshots = store.snapshots()?;
+            snapshots.reverse();
+            snapshots
+        }
+    };
+
+    if snapshots.is_empty() {
+        println!("No backups stored");
+    }
+
+    for snapshot in snapshots {
+        println!(
+            "{}  {}  {:<7}  {}",
+            snapshot.short_id(),
+            snapshot.created_local(),
+            snapshot.reason,
+            snapshot.path.display()
+        );
+    }
+
+    Ok(())
+}
+
+
+fn show(store: &BackupStore, args: &ShowArgs) -> Result<()> {
+    let snapshot = store.find(&args.id)?;
+    let savefile = store.load(&snapshot)?;
+
+    println!("Backup:  {}", snapshot.hash);
+    println!("Path:    {}", snapshot.path.display());
+    println!("Created: {}", snapshot.created_local());
+    println!("Reason:  {}", snapshot.reason);
+    println!("Size:    {} bytes", snapshot.size);
+
+    if !args.diff {
+        let info = SaveInfo::new(&savefile, &Section::ALL);
+        print!("\n{}", render_table(&info)?);
+        return Ok(());
+    }
+
+    let current = Savefile::from_path(&snapshot.path)
+        .with_context(|| format!("Failed to read current {}", snapshot.path.display()))?;
+
+    let changes = current.diff(&savefile);
+
+    println!("\nRestoring this version changes");
+    if changes.is_empty() {
+        println!("  nothing");
+    }
+    for change in changes {
+        println!("  {}", change);
+    }
+
+    Ok(())
+}
+
+
+fn restore(store: &BackupStore, args: &RestoreArgs) -> Result<()> {
+    let snapshot = store.find(&args.id)?;
+    let target = args.to.as_ref().unwrap_or(&snapshot.path);
+
+    let data = store.read(&snapshot)?;
+
+    if !args.yes && fs::read(target).is_ok_and(|current| current != data) {
+        confirm_overwrite(target)?;
+    }
+
+    for warning in write_data(store, target, &data, args.steam_cloud, Reason::Restore)?.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    println!("Restored {} to {}", snapshot.short_id(), target.display());
+
+    Ok(())
+}
+
+
+fn prune(store: &BackupStore, args: &PruneArgs) -> Result<()> {
+    let retention = Retention {
+        keep_last: args.keep_last,
+        keep_daily: args.keep_daily,
+        keep_within: args.keep_within,
+    };
+
+    if retention.keep_last.is_none()
+        && retention.keep_daily.is_none()
+        && retention.keep_within.is_none()
+    {
+        bail!("Refusing to remove all backups, use --keep-last, --keep-daily or --keep-within");
+    }
+
+    let removed = store.prune(&retention, Utc::now(), args.dry_run)?;
+
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    for snapshot in &removed {
+        println!(
+            "{} {}  {}  {}",
+            verb,
+            snapshot.short_id(),
+            snapshot.created_local(),
+            snapshot.path.display()
+        );
+    }
+    println!("{} {} backups", verb, removed.len());
+
+    Ok(())
+}
+
+
+impl BackupStore {
+    pub fn open() -> Result<Self> {
+        Self::at(DIRS.data_local_dir().join("backups"))
+    }
+
+    pub fn at<P>(dir: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join("objects"))
+            .with_context(|| format!("Failed to create backup store {}", dir.display()))?;
+
+        Ok(Self { dir })
+    }
+
+    /// Stores the current content of the file at `path`.
+    ///
+    /// Returns `None` if the content didn't change since the last stored version.
+    pub fn snapshot(&self, path: &Path, reason: Reason) -> Result<Option<Snapshot>> {
+        let path = fs::canonicalize(path)?;
+        let data = fs::read(&path)?;
+        let hash = hash(&data);
+
+        let latest = self.history(&path)?.into_iter().next();
+        if latest.is_some_and(|latest| latest.hash == hash) {
+            return Ok(None);
+        }
+
+        let object_path = self.object_path(&hash);
+        if !object_path.exists() {
+            fs::write(&object_path, &data)?;
+        }
+
+        let snapshot = Snapshot {
+            hash,
+            path,
+            created: Utc::now(),
+            reason,
+            size: data.len() as u64,
+        };
+
+        let mut index = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(self.index_path())?;
+        writeln!(index, "{}", serde_json::to_string(&snapshot)?)?;
+
+        Ok(Some(snapshot))
+    }
+
+    /// Stores the current content of the file at `path` like [`BackupStore::snapshot`], but
+    /// returns the version holding it even if it was stored before
+    pub fn back_up(&self, path: &Path, reason: Reason) -> Result<Snapshot> {
+        match self.snapshot(path, reason)? {
+            Some(snapshot) => Ok(snapshot),
+            None => self
+                .history(path)?
+                .into_iter()
+                .next()
+                .context("Backup store lost the latest version"),
+        }
+    }
+
+    /// All versions in the order they were stored
+    pub fn snapshots(&self) -> Result<Vec<Snapshot>> {
+        let index_path = self.index_path();
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(&index_path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Corrupt backup index"))
+            .collect()
+    }
+
+    /// Versions of the file at `path`, newest first
+    pub fn history(&self, path: &Path) -> Result<Vec<Snapshot>> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+
+        let mut snapshots = self.snapshots()?;
+        snapshots.retain(|snapshot| snapshot.path == path);
+        snapshots.reverse();
+
+        Ok(snapshots)
+    }
+
+    /// Finds the newest version whose hash starts with `id`
+    pub fn find(&self, id: &str) -> Result<Snapshot> {
+        let id = id.to_lowercase();
+
+        let matching = self
+            .snapshots()?
+            .into_iter()
+            .filter(|snapshot| snapshot.hash.starts_with(&id))
+            .collect::<Vec<_>>();
+
+        let hashes = matching
+            .iter()
+            .map(|snapshot| &snapshot.hash)
+            .collect::<HashSet<_>>();
+
+        match hashes.len() {
+            0 => bail!("No backup matches {}", id),
+            1 => Ok(matching.last().cloned().unwrap()),
+            _ => bail!("{} matches {} different backups", id, hashes.len()),
+        }
+    }
+
+    pub fn read(&self, snapshot: &Snapshot) -> Result<Vec<u8>> {
+        fs::read(self.object_path(&snapshot.hash))
+            .with_context(|| format!("Backup {} is missing", snapshot.hash))
+    }
+
+    /// Parses a stored version, its path is set to the path it was stored from
+    pub fn load(&self, snapshot: &Snapshot) -> Result<Savefile> {
+        let mut savefile = Savefile::from_reader(Cursor::new(self.read(snapshot)?))?;
+        savefile.path = snapshot.path.clone();
+
+        Ok(savefile)
+    }
+
+    /// Removes versions not kept by `retention` and contents no version refers to anymore
+    pub fn prune(
+        &self,
+        retention: &Retention,
+        now: DateTime<Utc>,
+        dry_run: bool,
+    ) -> Result<Vec<Snapshot>> {
+        let snapshots = self.snapshots()?;
+
+        let mut by_path = HashMap::<&Path, Vec<usize>>::new();
+        for (idx, snapshot) in snapshots.iter().enumerate() {
+            by_path.entry(&snapshot.path).or_default().push(idx);
+        }
+
+        let mut keep = vec![false; snapshots.len()];
+        for indices in by_path.values() {
+            // newest first
+            let history = indices.iter().rev().map(|&idx| (idx, &snapshots[idx]));
+            for idx in retention.kept(history, now) {
+                keep[idx] = true;
+            }
+        }
+
+        let (kept, removed): (Vec<_>, Vec<_>) =
+            snapshots.into_iter().zip(keep).partition(|(_, keep)| *keep);
+        let kept = kept
+            .into_iter()
+            .map(|(snapshot, _)| snapshot)
+            .collect::<Vec<_>>();
+        let removed = removed
+            .into_iter()
+            .map(|(snapshot, _)| snapshot)
+            .collect::<Vec<_>>();
+
+        if dry_run || removed.is_empty() {
+            return Ok(removed);
+        }
+
+        // an interrupted prune doesn't lose the index
+        let mut index = String::new();
+        for snapshot in &kept {
+            index.push_str(&serde_json::to_string(snapshot)?);
+            index.push('\n');
+        }
+        write_atomic(&self.index_path(), index)?;
+
+        let referenced = kept
+            .iter()
+            .map(|snapshot| snapshot.hash.as_str())
+            .collect::<HashSet<_>>();
+        for snapshot in &removed {
+            if !referenced.contains(snapshot.hash.as_str()) {
+                let _ = fs::remove_file(self.object_path(&snapshot.hash));
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_FILE_NAME)
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.dir.join("objects").join(hash)
+    }
+}
+
+
+impl Retention {
+    /// Indices of the kept versions of one savefile, given newest first
+    fn kept<'a, I>(&self, history: I, now: DateTime<Utc>) -> Vec<usize>
+    where
+        I: Iterator<Item = (usize, &'a Snapshot)>,
+    {
+        let mut kept = Vec::new();
+        let mut days = Vec::new();
+
+        for (position, (idx, snapshot)) in history.enumerate() {
+            let by_last = self.keep_last.is_some_and(|last| position < last);
+
+            let by_age = self
+                .keep_within
+                .is_some_and(|age| now - snapshot.created <= age);
+
+            let day = snapshot.created.with_timezone(&Local).date_naive();
+            let by_day = match self.keep_daily {
+                Some(daily) if !days.contains(&day) && days.len() < daily => {
+                    days.push(day);
+                    true
+                }
+                _ => false,
+            };
+
+            if by_last || by_age || by_day {
+                kept.push(idx);
+            }
+        }
+
+        kept
+    }
+}
+
+
+impl Snapshot {
+    pub fn short_id(&self) -> &str {
+        &self.hash[..SHORT_ID_LEN.min(self.hash.len())]
+    }
+
+    pub fn created_local(&self) -> String {
+        self.created
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+}
+
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Edit => "edit",
+            Self::Save => "save",
+            Self::Reload => "reload",
+            Self::Restore => "restore",
+            Self::Slot => "slot",
+        };
+
+        f.pad(name)
+    }
+}
+
+
+/// Hex encoded SHA-1 of `data`, which is also what Steam Cloud uses
+pub(crate) fn hash(data: &[u8]) -> String {
+    Sha1::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+
+pub(crate) fn parse_age(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .ok_or("missing unit, use m, h, d or w")?;
+    let (amount, unit) = value.split_at(split);
+
+    let amount = amount
+        .parse::<i64>()
+        .map_err(|_| format!("invalid amount {}", amount))?;
+
+    match unit {
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        "w" => Ok(Duration::weeks(amount)),
+        _ => Err(format!("unknown unit {}, use m, h, d or w", unit)),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{temp_savefile, test_savefile, TEST_FILE};
+
+    fn snapshot_at(path: &str, created: DateTime<Utc>) -> Snapshot {
+        Snapshot {
+            hash: hash(created.to_rfc3339().as_bytes()),
+            path: PathBuf::from(path),
+            created,
+            reason: Reason::Edit,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn stores_each_version_once() {
+        let (dir, path) = temp_savefile();
+        let store = BackupStore::at(dir.path().join("backups")).unwrap();
+
+        let first = store.snapshot(&path, Reason::Edit).unwrap().unwrap();
+        assert!(store.snapshot(&path, Reason::Save).unwrap().is_none());
+        assert_eq!(store.back_up(&path, Reason::Save).unwrap(), first);
+
+        let mut savefile = Savefile::from_path(&path).unwrap();
+        savefile.journey_count += 1;
+        savefile.write(fs::File::create(&path).unwrap()).unwrap();
+        let second = store.snapshot(&path, Reason::Save).unwrap().unwrap();
+
+        assert_ne!(first.hash, second.hash);
+        assert_eq!(store.history(&path).unwrap(), [
+            second.clone(),
+            first.clone()
+        ]);
+        assert_eq!(store.find(&first.hash[..8]).unwrap(), first);
+        assert_eq!(store.read(&first).unwrap(), TEST_FILE);
+        assert_eq!(store.load(&second).unwrap().journey_count, 22);
+    }
+
+    #[test]
+    fn retention() {
+        let now = Utc::now();
+        let history = (0..10)
+            .map(|hours| snapshot_at("SAVE.BIN", now - Duration::hours(hours * 12)))
+            .collect::<Vec<_>>();
+        let indexed = || history.iter().enumerate();
+
+        let last = Retention {
+            keep_last: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(last.kept(indexed(), now), [0, 1, 2]);
+
+        let within = Retention {
+            keep_within: Some(Duration::hours(30)),
+            ..Default::default()
+        };
+        assert_eq!(within.kept(indexed(), now), [0, 1, 2]);
+
+        let daily = Retention {
+            keep_daily: Some(2),
+            ..Default::default()
+        };
+        let kept = daily.kept(indexed(), now);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0], 0);
+    }
+
+    #[test]
+    fn prune_removes_unreferenced_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::at(dir.path().join("backups")).unwrap();
+
+        let path = dir.path().join("SAVE.BIN");
+        let mut snapshots = Vec::new();
+        for journey_count in 0..3 {
+            let mut savefile = test_savefile();
+            savefile.journey_count = journey_count;
+            savefile.write(fs::File::create(&path).unwrap()).unwrap();
+            snapshots.push(store.snapshot(&path, Reason::Edit).unwrap().unwrap());
+        }
+
+        let retention = Retention {
+            keep_last: Some(1),
+            ..Default::default()
+        };
+
+        let removed = store.prune(&retention, Utc::now(), true).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(store.snapshots().unwrap().len(), 3);
+
+        store.prune(&retention, Utc::now(), false).unwrap();
+        assert_eq!(store.snapshots().unwrap(), [snapshots[2].clone()]);
+        assert!(store.read(&snapshots[0]).is_err());
+        assert!(store.read(&snapshots[2]).is_ok());
+    }
+
+    #[test]
+    fn ages() {
+        assert_eq!(parse_age("12h"), Ok(Duration::hours(12)));
+        assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+        assert_eq!(parse_age("90m"), Ok(Duration::minutes(90)));
+        assert_eq!(
+            parse_age("30"),
+            Err("missing unit, use m, h, d or w".to_string())
+        );
+        assert!(parse_age("3y").is_err());
+    }
+}
@@ -0,0 +1,536 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::{RobeColor, Savefile, MAX_ROBE_TIER, MAX_SCARF_LENGTH};
+use serde::Deserialize;
+
+use crate::info::{CompanionLists, LevelStatus, SaveInfo, Section};
+use crate::locate::savefile_or_default;
+use crate::AppArgs;
+
+
+/// Cells of the symbol grid in each direction
+const SYMBOL_CELLS: u32 = 7;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to show, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    /// Writes the card to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    /// Built-in colors, overridden by --theme-file
+    #[arg(
+        long,
+        default_value = "dark",
+        value_parser = PossibleValuesParser::new(["dark", "light"])
+    )]
+    theme: String,
+
+    /// TOML file with colors and font family, e.g. `accent = "#ff8800"`
+    #[arg(long)]
+    theme_file: Option<PathBuf>,
+
+    /// Width of the card in pixels, the height follows from the content
+    #[arg(long, default_value_t = 640, value_parser = clap::value_parser!(u32).range(400..))]
+    width: u32,
+
+    /// Font size in pixels, everything else is scaled along
+    #[arg(long, default_value_t = 14, value_parser = clap::value_parser!(u32).range(8..=40))]
+    font_size: u32,
+
+    /// Number of companions listed, current ones first
+    #[arg(long, default_value_t = 5)]
+    companions: usize,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Theme {
+    pub background: String,
+    pub foreground: String,
+    pub muted: String,
+    pub accent: String,
+    pub found: String,
+    pub missing: String,
+    pub red_robe: String,
+    pub white_robe: String,
+    pub font_family: String,
+}
+
+
+/// Colors and font family set in a theme file, anything missing is taken from the base theme
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    background: Option<String>,
+    foreground: Option<String>,
+    muted: Option<String>,
+    accent: Option<String>,
+    found: Option<String>,
+    missing: Option<String>,
+    red_robe: Option<String>,
+    white_robe: Option<String>,
+    font_family: Option<String>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Size {
+    pub width: u32,
+    pub font_size: u32,
+}
+
+
+/// SVG elements positioned on a grid derived from the font size
+struct Card<'a> {
+    theme: &'a Theme,
+    size: Size,
+    out: String,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let savefile = Savefile::from_path(path)?;
+
+    let mut theme = Theme::named(&sub_args.theme);
+    if let Some(theme_file) = &sub_args.theme_file {
+        theme.apply_file(theme_file)?;
+    }
+
+    let size = Size {
+        width: sub_args.width,
+        font_size: sub_args.font_size,
+    };
+
+    let svg = render(&savefile, &theme, size, sub_args.companions);
+
+    match &sub_args.output {
+        Some(output) => fs::write(output, svg)
+            .with_context(|| format!("Failed to write {}", output.display()))?,
+        None => print!("{}", svg),
+    }
+
+    Ok(())
+}
+
+
+impl Theme {
+    pub fn named(name: &str) -> Self {
+        let (background, foreground, muted, accent, found, missing) = match name {
+            "light" => (
+                "#f6f1e7", "#2b2a33", "#857f72", "#b5651d", "#c9822f", "#ddd4c2",
+            ),
+            _ => (
+                "#1d1f2b", "#f2efe6", "#8a8fa3", "#e0a84e", "#e0a84e", "#3a3d4d",
+            ),
+        };
+
+        Self {
+            background: background.to_string(),
+            foreground: foreground.to_string(),
+            muted: muted.to_string(),
+            accent: accent.to_string(),
+            found: found.to_string(),
+            missing: missing.to_string(),
+            red_robe: "#b8322a".to_string(),
+            white_robe: "#ece6d8".to_string(),
+            font_family: "Helvetica, Arial, sans-serif".to_string(),
+        }
+    }
+
+    fn apply_file(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read theme {}", path.display()))?;
+        let file = toml::from_str::<ThemeFile>(&content)
+            .with_context(|| format!("Invalid theme {}", path.display()))?;
+
+        let fields = [
+            (&mut self.background, file.background),
+            (&mut self.foreground, file.foreground),
+            (&mut self.muted, file.muted),
+            (&mut self.accent, file.accent),
+            (&mut self.found, file.found),
+            (&mut self.missing, file.missing),
+            (&mut self.red_robe, file.red_robe),
+            (&mut self.white_robe, file.white_robe),
+            (&mut self.font_family, file.font_family),
+        ];
+
+        for (field, value) in fields {
+            if let Some(value) = value {
+                // values end up in attributes, so quotes would break the card
+                if value.contains(['"', '<', '&']) {
+                    bail!("Invalid theme value {} in {}", value, path.display());
+                }
+                *field = value;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Draws the profile card of `savefile` as SVG
+pub(crate) fn render(savefile: &Savefile, theme: &Theme, size: Size, companions: usize) -> String {
+    let info = SaveInfo::new(savefile, &Section::ALL);
+    let mut card = Card::new(theme, size);
+
+    let f = size.font_size;
+    let pad = 2 * f;
+    let line = f * 8 / 5;
+
+    // symbol on the left, stats next to it
+    let cell = f * 5 / 7;
+    let symbol_lines = savefile.symbol.to_string();
+    card.symbol(&symbol_lines, pad, pad, cell);
+
+    let x = pad + SYMBOL_CELLS * cell + pad;
+    let mut y = pad + f * 3 / 2;
+
+    card.text(
+        x,
+        y,
+        f * 3 / 2,
+        &theme.accent,
+        true,
+        &format!("{} journeys", savefile.journey_count),
+    );
+    y += line;
+    card.text(
+        x,
+        y,
+        f,
+        &theme.muted,
+        false,
+        &savefile.current_level.to_string(),
+    );
+
+    y += line + f / 2;
+    let robe = match savefile.robe.color() {
+        RobeColor::Red => &theme.red_robe,
+        RobeColor::White => &theme.white_robe,
+    };
+    card.rect(x, y - f, f, f, f / 5, robe);
+    card.text(
+        x + f * 3 / 2,
+        y,
+        f,
+        &theme.foreground,
+        false,
+        &format!("{} robe", savefile.robe.color()),
+    );
+    let pips_x = x + f * 8;
+    for tier in 1..=MAX_ROBE_TIER {
+        let color = if tier <= savefile.robe.tier() {
+            &theme.found
+        } else {
+            &theme.missing
+        };
+        card.circle(
+            pips_x + (tier - 1) * f + f / 2,
+            y - f / 2 + 1,
+            f * 3 / 10,
+            color,
+        );
+    }
+
+    y += line;
+    let scarf_length = *savefile.scarf_length.as_ref();
+    card.text(
+        x,
+        y,
+        f,
+        &theme.foreground,
+        false,
+        &format!("Scarf {}", scarf_length),
+    );
+    let bar_width = size.width.saturating_sub(pips_x + pad).max(f);
+    card.rect(
+        pips_x,
+        y - f * 2 / 3,
+        bar_width,
+        f / 2,
+        f / 4,
+        &theme.missing,
+    );
+    card.rect(
+        pips_x,
+        y - f * 2 / 3,
+        bar_width * scarf_length.min(MAX_SCARF_LENGTH) / MAX_SCARF_LENGTH,
+        f / 2,
+        f / 4,
+        &theme.found,
+    );
+
+    // glyphs and murals in two columns
+    let mut y = (y + f).max(pad + SYMBOL_CELLS * cell) + pad;
+    let column = (size.width - 3 * pad) / 2;
+    let glyphs = info.glyphs.unwrap_or_default();
+    let murals = info.murals.unwrap_or_default();
+
+    let glyphs_end = card.levels("Glyphs", &glyphs, pad, y, column);
+    let murals_end = card.levels("Murals", &murals, 2 * pad + column, y, column);
+    y = glyphs_end.max(murals_end) + pad;
+
+    // most recently met companions first
+    let lists = info
+        .companions
+        .unwrap_or_else(|| CompanionLists::new(savefile));
+    let names = lists
+        .current
+        .iter()
+        .rev()
+        .chain(lists.past.iter().rev())
+        .take(companions)
+        .map(|companion| companion.name.as_str())
+        .collect::<Vec<_>>();
+
+    if !names.is_empty() {
+        card.text(pad, y, f, &theme.accent, true, "Companions");
+        y += line;
+        for name in names {
+            card.text(pad, y, f, &theme.foreground, false, name);
+            y += line;
+        }
+        y += pad - line + f / 2;
+    }
+
+    card.finish(y)
+}
+
+
+impl<'a> Card<'a> {
+    fn new(theme: &'a Theme, size: Size) -> Self {
+        Self {
+            theme,
+            size,
+            out: String::new(),
+        }
+    }
+
+    /// Draws the filled cells of the symbol's text grid, each cell is two characters wide
+    fn symbol(&mut self, lines: &str, x: u32, y: u32, cell: u32) {
+        for (row, line) in lines.lines().enumerate() {
+            let cells = line.chars().collect::<Vec<_>>();
+            for (col, pair) in cells.chunks(2).enumerate() {
+                if pair.iter().all(|&c| c == '█') {
+                    let color = self.theme.foreground.clone();
+                    self.rect(
+                        x + col as u32 * cell,
+                        y + row as u32 * cell,
+                        cell,
+                        cell,
+                        0,
+                        &color,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draws a titled list of levels with a dot per glyph or mural, returns where it ends
+    fn levels(&mut self, title: &str, levels: &[LevelStatus], x: u32, y: u32, width: u32) -> u32 {
+        let f = self.size.font_size;
+        let line = f * 8 / 5;
+
+        let found = levels
+            .iter()
+            .flat_map(|level| &level.found)
+            .filter(|&&found| found)
+            .count();
+        let total = levels.iter().map(|level| level.found.len()).sum::<usize>();
+
+        let accent = self.theme.accent.clone();
+        self.text(
+            x,
+            y,
+            f,
+            &accent,
+            true,
+            &format!("{} {}/{}", title, found, total),
+        );
+
+        let dots_x = x + f * 8;
+        let most = levels
+            .iter()
+            .map(|level| level.found.len())
+            .max()
+            .unwrap_or(1)
+            .max(1) as u32;
+        // large fonts on narrow cards leave no room, the dots then overlap but stay in order
+        let spacing = (width.saturating_sub(f * 8) / most).clamp(1, f);
+
+        let mut y = y;
+        for level in levels.iter().filter(|level| !level.found.is_empty()) {
+            y += line;
+            let muted = self.theme.muted.clone();
+            self.text(x, y, f, &muted, false, &level.level);
+
+            for (idx, &found) in level.found.iter().enumerate() {
+                let color = if found {
+                    self.theme.found.clone()
+                } else {
+                    self.theme.missing.clone()
+                };
+                self.circle(
+                    dots_x + idx as u32 * spacing + spacing / 2,
+                    y - f / 3,
+                    f / 3,
+                    &color,
+                );
+            }
+        }
+
+        y
+    }
+
+    fn text(&mut self, x: u32, y: u32, size: u32, color: &str, bold: bool, text: &str) {
+        let weight = if bold { " font-weight=\"bold\"" } else { "" };
+        let _ = writeln!(
+            self.out,
+            "  <text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\"{}>{}</text>",
+            x,
+            y,
+            size,
+            color,
+            weight,
+            escape(text)
+        );
+    }
+
+    fn rect(&mut self, x: u32, y: u32, width: u32, height: u32, radius: u32, color: &str) {
+        let _ = writeln!(
+            self.out,
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\"/>",
+            x, y, width, height, radius, color
+        );
+    }
+
+    fn circle(&mut self, x: u32, y: u32, radius: u32, color: &str) {
+        let _ = writeln!(
+            self.out,
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+            x, y, radius, color
+        );
+    }
+
+    fn finish(self, height: u32) -> String {
+        let width = self.size.width;
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\" font-family=\"{font}\">\n  \
+             <rect width=\"{w}\" height=\"{h}\" rx=\"{r}\" fill=\"{bg}\"/>\n{body}</svg>\n",
+            w = width,
+            h = height,
+            r = self.size.font_size,
+            font = self.theme.font_family,
+            bg = self.theme.background,
+            body = self.out,
+        )
+    }
+}
+
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    /// Compares with the file in `testdata`, `UPDATE_GOLDEN=1` rewrites it instead
+    fn assert_golden(name: &str, rendered: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(name);
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, rendered).unwrap();
+        }
+
+        let golden = fs::read_to_string(&path).unwrap();
+        assert!(
+            golden == rendered,
+            "{} differs, run with UPDATE_GOLDEN=1 to update it",
+            path.display()
+        );
+    }
+
+    #[test]
+    fn dark_card() {
+        let size = Size {
+            width: 640,
+            font_size: 14,
+        };
+        let svg = render(&test_savefile(), &Theme::named("dark"), size, 5);
+
+        assert_golden("card-dark.svg", &svg);
+    }
+
+    #[test]
+    fn light_card() {
+        let size = Size {
+            width: 480,
+            font_size: 12,
+        };
+        let svg = render(&test_savefile(), &Theme::named("light"), size, 3);
+
+        assert_golden("card-light.svg", &svg);
+    }
+
+    #[test]
+    fn large_font_on_narrow_card() {
+        let size = Size {
+            width: 400,
+            font_size: 40,
+        };
+        let svg = render(&test_savefile(), &Theme::named("dark"), size, 5);
+
+        let dots = svg
+            .lines()
+            .filter(|line| line.contains("<circle"))
+            .map(|line| line.split(" r=").next().unwrap())
+            .collect::<Vec<_>>();
+        let mut distinct = dots.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), dots.len());
+    }
+
+    #[test]
+    fn theme_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("theme.toml");
+
+        fs::write(&path, "accent = \"#ff8800\"\nfont_family = \"Fira Sans\"").unwrap();
+        let mut theme = Theme::named("dark");
+        theme.apply_file(&path).unwrap();
+        assert_eq!(theme.accent, "#ff8800");
+        assert_eq!(theme.font_family, "Fira Sans");
+        assert_eq!(theme.background, Theme::named("dark").background);
+
+        fs::write(&path, "accent = \"\\\" onload=\\\"\"").unwrap();
+        assert!(Theme::named("dark").apply_file(&path).is_err());
+
+        fs::write(&path, "colour = \"red\"").unwrap();
+        assert!(Theme::named("dark").apply_file(&path).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::Cursor;
+
+use jrny_save::{
+    RobeColor, Savefile, MAX_COLLECTED_SYMBOLS, MAX_ROBE_TIER, MAX_SCARF_LENGTH, MAX_SYMBOL_ID,
+    MIN_ROBE_TIER,
+};
+
+
+/// Values the game would not write, or that don't survive writing and reading the savefile
+pub(crate) fn problems(savefile: &Savefile) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let mut check = |valid: bool, problem: String| {
+        if !valid {
+            problems.push(problem);
+        }
+    };
+
+    let tier = savefile.robe.tier();
+    check(
+        (MIN_ROBE_TIER..=MAX_ROBE_TIER).contains(&tier),
+        format!(
+            "robe_tier {} is not between {} and {}",
+            tier, MIN_ROBE_TIER, MAX_ROBE_TIER
+        ),
+    );
+
+    let scarf_length = *savefile.scarf_length.as_ref();
+    check(
+        scarf_length <= MAX_SCARF_LENGTH,
+        format!(
+            "scarf_length {} is above {}",
+            scarf_length, MAX_SCARF_LENGTH
+        ),
+    );
+
+    let symbol = *savefile.symbol.as_ref();
+    check(
+        symbol <= MAX_SYMBOL_ID,
+        format!("symbol {} is above {}", symbol, MAX_SYMBOL_ID),
+    );
+
+    check(
+        savefile.collected_symbols <= MAX_COLLECTED_SYMBOLS,
+        format!(
+            "collected_symbols {} is above {}",
+            savefile.collected_symbols, MAX_COLLECTED_SYMBOLS
+        ),
+    );
+
+    let companions = savefile.companions.iter().count();
+    check(
+        savefile.companions_met as usize <= companions,
+        format!(
+            "companions_met {} is above the {} companions in the list",
+            savefile.companions_met, companions
+        ),
+    );
+
+    let mut data = Vec::new();
+    let roundtrip = savefile
+        .write(Cursor::new(&mut data))
+        .map_err(anyhow::Error::from)
+        .and_then(|_| Ok(Savefile::from_reader(Cursor::new(&data))?));
+    match roundtrip {
+        Ok(read) => check(
+            savefile.diff(&read).is_empty(),
+            "the savefile changes when written".to_string(),
+        ),
+        Err(err) => check(false, format!("the savefile can not be written: {}", err)),
+    }
+
+    problems
+}
+
+
+/// Fields that disagree with the fields they depend on, like consistent edits would keep them
+pub(crate) fn inconsistencies(savefile: &Savefile) -> Vec<String> {
+    let mut inconsistencies = Vec::new();
+
+    let mut check = |consistent: bool, inconsistency: String| {
+        if !consistent {
+            inconsistencies.push(inconsistency);
+        }
+    };
+
+    let collected = savefile.glyphs.collected();
+    let glyphs = savefile
+        .glyphs
+        .all()
+        .map(|(_, glyphs)| glyphs.len())
+        .sum::<usize>();
+    check(
+        savefile.robe.color() != RobeColor::White || collected == glyphs,
+        format!(
+            "the robe is white, but only {} of {} glyphs are collected",
+            collected, glyphs
+        ),
+    );
+
+    check(
+        savefile.total_collected_symbols >= savefile.collected_symbols,
+        format!(
+            "total_collected_symbols {} is below collected_symbols {}",
+            savefile.total_collected_symbols, savefile.collected_symbols
+        ),
+    );
+
+    check(
+        savefile.total_companions_met >= savefile.companions_met,
+        format!(
+            "total_companions_met {} is below companions_met {}",
+            savefile.total_companions_met, savefile.companions_met
+        ),
+    );
+
+    inconsistencies
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use sha1::{Digest, Sha1};
+use tracing::debug;
+
+use crate::edit::write_atomic;
+use crate::vdf::Value;
+
+
+const CACHE_FILE_NAME: &str = "remotecache.vdf";
+
+/// How many directories above a savefile to look for the cache
+const SEARCH_DEPTH: usize = 3;
+
+
+/// Steam Cloud's local record of a savefile, read from the `remotecache.vdf` next to it.
+///
+/// Steam compares this record with the file on disk to decide whether to upload the local file
+/// or to restore the cloud copy, so it has to be updated after every write.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteCache {
+    path: PathBuf,
+    root: Value,
+    app_id: String,
+    entry: String,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileMetadata {
+    pub size: u64,
+    pub time: u64,
+    pub sha: String,
+}
+
+
+impl RemoteCache {
+    /// Looks for a `remotecache.vdf` with an entry for the savefile at `savefile`
+    pub fn find(savefile: &Path) -> Result<Option<Self>> {
+        for dir in savefile.ancestors().skip(1).take(SEARCH_DEPTH) {
+            let path = dir.join(CACHE_FILE_NAME);
+            if !path.exists() {
+                continue;
+            }
+
+            let root = Value::from_path(&path)?;
+
+            // the cache holds a single object named after the app id
+            let Some((app_id, files)) = root.entries().first() else {
+                continue;
+            };
+
+            let Some(entry) = entry_name(dir, savefile, files) else {
+                debug!("{} has no entry for {}", path.display(), savefile.display());
+                continue;
+            };
+
+            return Ok(Some(Self {
+                app_id: app_id.clone(),
+                path,
+                root,
+                entry,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn cached(&self) -> Option<FileMetadata> {
+        let entry = self.root.get(&self.app_id)?.get(&self.entry)?;
+        let field = |name| entry.get(name).and_then(Value::as_str);
+
+        Some(FileMetadata {
+            size: field("size")?.parse().ok()?,
+            time: field("localtime").or_else(|| field("time"))?.parse().ok()?,
+            sha: field("sha")?.to_lowercase(),
+        })
+    }
+
+    /// Describes how the cached metadata differs from the file on disk
+    pub fn mismatches(&self, savefile: &Path) -> Result<Vec<String>> {
+        let actual = FileMetadata::from_path(savefile)?;
+
+        let Some(cached) = self.cached() else {
+            return Ok(vec![format!("entry {} is incomplete", self.entry)]);
+        };
+
+        let mut mismatches = Vec::new();
+
+        if cached.size != actual.size {
+            mismatches.push(format!(
+                "size is {} but Steam Cloud expects {}",
+                actual.size, cached.size
+            ));
+        }
+
+        if cached.sha != actual.sha {
+            mismatches.push(format!(
+                "hash is {} but Steam Cloud expects {}",
+                actual.sha, cached.sha
+            ));
+        }
+
+        Ok(mismatches)
+    }
+
+    /// Records the current state of the savefile on disk and writes the cache back
+    pub fn update(&mut self, savefile: &Path) -> Result<()> {
+        let actual = FileMetadata::from_path(savefile)?;
+
+        let entry = self
+            .root
+            .get_mut(&self.app_id)
+            .and_then(|files| files.get_mut(&self.entry))
+            .context("Steam Cloud entry disappeared")?;
+
+        entry.set("size", Value::String(actual.size.to_string()));
+        entry.set("localtime", Value::String(actual.time.to_string()));
+        entry.set("time", Value::String(actual.time.to_string()));
+        entry.set("sha", Value::String(actual.sha));
+
+        // Steam reads the cache on its own schedule, it must never see half a file
+        write_atomic(&self.path, self.root.to_string())
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+
+impl FileMetadata {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let data = fs::read(path)?;
+        let modified = fs::metadata(path)?.modified()?;
+
+        Ok(Self {
+            size: data.len() as u64,
+            time: modified.duration_since(UNIX_EPOCH)?.as_secs(),
+            sha: Sha1::digest(&data)
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        })
+    }
+}
+
+
+/// Finds the entry name of a savefile, which is its path relative to the `remote` directory
+fn entry_name(cache_dir: &Path, savefile: &Path, files: &Value) -> Option<String> {
+    let relative = savefile
+        .strip_prefix(cache_dir.join("remote"))
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    files
+        .entries()
+        .iter()
+        .map(|(name, _)| name)
+        .find(|name| name.eq_ignore_ascii_case(&relative))
+        .cloned()
+}
+
+
+/// Checks the Steam Cloud cache of a savefile before it gets overwritten.
+///
+/// Returns the cache to update after writing and warnings about a cache that's already out of
+/// date.
+pub(crate) fn prepare_save(savefile: &Path) -> Result<(Option<RemoteCache>, Vec<String>)> {
+    let Some(cache) = RemoteCache::find(savefile)? else {
+        return Ok((None, Vec::new()));
+    };
+
+    let warnings = if savefile.exists() {
+        cache
+            .mismatches(savefile)?
+            .into_iter()
+            .map(|mismatch| {
+                format!(
+                    "Steam Cloud metadata in {} is out of date, {}",
+                    cache.path().display(),
+                    mismatch
+                )
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok((Some(cache), warnings))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{temp_savefile, TEST_FILE};
+
+    const REMOTE_CACHE: &str = include_str!("../testdata/remotecache.vdf");
+
+    fn fake_userdata() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let app_dir = dir.path().join("12345/638230");
+
+        fs::create_dir_all(app_dir.join("remote")).unwrap();
+        fs::write(app_dir.join("remotecache.vdf"), REMOTE_CACHE).unwrap();
+        fs::write(app_dir.join("remote/SAVE.BIN"), TEST_FILE).unwrap();
+
+        (dir, app_dir.join("remote/SAVE.BIN"))
+    }
+
+    #[test]
+    fn finds_entry() {
+        let (_dir, savefile) = fake_userdata();
+
+        let cache = RemoteCache::find(&savefile).unwrap().unwrap();
+        let cached = cache.cached().unwrap();
+
+        assert_eq!(cached.size, 32768);
+        assert_eq!(cached.time, 1690977600);
+        assert_eq!(cached.sha, "2d1c4b6f5a8e9d0c3b7a6f5e4d3c2b1a09f8e7d6");
+    }
+
+    #[test]
+    fn warns_and_updates() {
+        let (_dir, savefile) = fake_userdata();
+
+        let (cache, warnings) = prepare_save(&savefile).unwrap();
+        let mut cache = cache.unwrap();
+
+        // the fixture has the right size but an outdated hash
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("hash is b750ce2ef1b6d092404842ffe36af2ba0ca157ad"));
+
+        cache.update(&savefile).unwrap();
+
+        let cache = RemoteCache::find(&savefile).unwrap().unwrap();
+        assert!(cache.mismatches(&savefile).unwrap().is_empty());
+
+        // unrelated entries are kept
+        let written = Value::from_path(cache.path()).unwrap();
+        assert_eq!(
+            written.get("638230").unwrap().get("ChangeNumber").unwrap(),
+            &Value::String("42".to_string())
+        );
+        assert_eq!(
+            written
+                .get("638230")
+                .unwrap()
+                .get("SAVE.BIN")
+                .unwrap()
+                .get("remotetime"),
+            Some(&Value::String("1690977600".to_string()))
+        );
+    }
+
+    #[test]
+    fn no_cache() {
+        let (_dir, savefile) = temp_savefile();
+
+        assert!(RemoteCache::find(&savefile).unwrap().is_none());
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+use unicode_width::UnicodeWidthStr;
+
+use crate::info::CompanionLists;
+use crate::locate::default_savefile;
+use crate::roster::{self, Roster, RosterEntry};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to list the companions of, defaults to the one last opened in the TUI.
+    /// Its companions are added to the roster
+    path: Option<PathBuf>,
+
+    /// Lists every companion ever seen instead of only the ones in the savefile
+    #[arg(long, short)]
+    all: bool,
+
+    /// Only lists companions whose name or Steam ID contains the given text
+    #[arg(long, short)]
+    search: Option<String>,
+
+    #[arg(
+        long,
+        short,
+        default_value = "table",
+        value_parser = PossibleValuesParser::new(["table", "json", "yaml"])
+    )]
+    format: String,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = match &sub_args.path {
+        Some(path) => Some(path.clone()),
+        None if sub_args.all => None,
+        None => Some(default_savefile()?),
+    };
+
+    // the savefile's companions are added first, so they are part of the listed roster
+    let savefile = path.map(Savefile::from_path).transpose()?;
+    if let Some(savefile) = &savefile {
+        roster::observe(savefile)?;
+    }
+
+    let roster = Roster::load()?;
+    let mut entries = roster.search(sub_args.search.as_deref().unwrap_or_default());
+
+    if let (Some(savefile), false) = (&savefile, sub_args.all) {
+        let lists = CompanionLists::new(savefile);
+        let ids = lists
+            .current
+            .iter()
+            .chain(&lists.past)
+            .map(|companion| companion.steam_id)
+            .collect::<Vec<_>>();
+        entries.retain(|entry| ids.contains(&entry.steam_id));
+    }
+
+    let output = match sub_args.format.as_ref() {
+        "json" => serde_json::to_string_pretty(&entries)? + "\n",
+        "yaml" => serde_yaml::to_string(&entries)?,
+        _ => render_table(&entries),
+    };
+
+    print!("{}", output);
+
+    Ok(())
+}
+
+
+fn render_table(entries: &[&RosterEntry]) -> String {
+    if entries.is_empty() {
+        return "No companions found\n".to_string();
+    }
+
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.name.width())
+        .max()
+        .unwrap_or_default()
+        .max(4);
+
+    let mut out = format!(
+        "{:<name_width$}  {:<16}  {:>6}  {:>4}  {:<16}  {}\n",
+        "Name", "Steam ID", "Symbol", "Seen", "First seen", "Last seen"
+    );
+
+    for entry in entries {
+        let padding = " ".repeat(name_width - entry.name.width());
+        out += &format!(
+            "{}{}  {:<16}  {:>6}  {:>4}  {}  {}\n",
+            entry.name,
+            padding,
+            entry.steam_id_v3(),
+            entry
+                .symbol
+                .map(|symbol| symbol.to_string())
+                .unwrap_or_default(),
+            entry.times_seen,
+            entry
+                .first_seen
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            entry
+                .last_seen
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+        );
+    }
+
+    out
+}
@@ -0,0 +1,62 @@
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::{Platform, Savefile};
+
+use crate::edit::{confirm_overwrite, differs_from};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    in_path: PathBuf,
+    out_path: PathBuf,
+
+    /// The platform the output file should be written for.
+    ///
+    /// Only the byte order of the known fields is converted, PlayStation output is untested on the
+    /// console.
+    #[arg(long, value_parser = PossibleValuesParser::new(["pc", "playstation"]))]
+    to: String,
+
+    /// Overwrites an existing output file without asking
+    #[arg(long, short)]
+    yes: bool,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let in_file = File::open(&sub_args.in_path)?;
+
+    let savefile = Savefile::from_reader(in_file)?;
+
+    let platform: Platform = sub_args.to.parse()?;
+
+    let new_savefile = savefile.convert_to(platform);
+
+    if platform == Platform::PlayStation {
+        eprintln!(
+            "Warning: the PlayStation layout is unverified, the game may not accept the file"
+        );
+    }
+
+    let mut data = Vec::new();
+    new_savefile.write(Cursor::new(&mut data))?;
+
+    if !sub_args.yes && differs_from(&sub_args.out_path, &data) {
+        confirm_overwrite(&sub_args.out_path)?;
+    }
+
+    fs::write(&sub_args.out_path, data)?;
+
+    println!(
+        "Converted {} savefile to {}",
+        savefile.platform, new_savefile.platform
+    );
+
+    Ok(())
+}
@@ -1,82 +1,765 @@
-use std::fs::{self, File};
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::io::{self, Cursor, IsTerminal, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
 use clap::builder::PossibleValuesParser;
 use clap::{value_parser, Parser as ArgParser};
-use jrny_save::{RobeColor, Savefile, LEVEL_NAMES};
+use jrny_save::{
+    Change, Edit, EditMode, RobeColor, Savefile, LEVEL_NAMES, MAX_COLLECTED_SYMBOLS,
+    MAX_COMPANIONS, MAX_COMPANION_SYMBOL_ID, MAX_JOURNEY_COUNT, MAX_ROBE_TIER, MAX_SCARF_LENGTH,
+    MAX_SYMBOL_ID, MAX_TOTAL_COUNT, MIN_ROBE_TIER,
+};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
 
-use crate::AppArgs;
+use crate::backup::{BackupStore, Reason};
+use crate::recipe::Recipe;
+use crate::{cloud, AppArgs};
 
 
 #[derive(Debug, Clone, ArgParser)]
 pub(crate) struct Args {
-    in_path: PathBuf,
-    out_path: PathBuf,
+    /// Input and output savefile, only the input with --in-place or --dry-run.
+    /// With --recipe any number of savefiles or glob patterns
+    #[arg(required = true, value_name = "PATHS")]
+    paths: Vec<PathBuf>,
 
-    #[arg(long, value_parser = value_parser!(u32).range(1..=32))]
+    /// Applies the changes of a TOML recipe, flags are applied after the recipe
+    #[arg(long, short)]
+    recipe: Option<PathBuf>,
+
+    /// Writes back to the input file, the replaced version is kept in the backup store
+    #[arg(long, short)]
+    in_place: bool,
+
+    /// Prints the fields that would change without writing anything
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+
+    /// Overwrites an existing output file without asking
+    #[arg(long, short)]
+    yes: bool,
+
+    /// Also change fields that depend on the edited ones
+    #[arg(long)]
+    consistent: bool,
+
+    /// Updates Steam Cloud's remotecache.vdf next to written savefiles
+    #[arg(long)]
+    steam_cloud: bool,
+
+    #[command(flatten)]
+    changes: Changes,
+}
+
+
+/// Field changes shared by the command line flags and the `[set]` table of recipes
+#[derive(Debug, Clone, Default, clap::Args, Deserialize)]
+#[group(skip)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Changes {
+    #[arg(long, value_parser = value_parser!(u64).range(0..=MAX_JOURNEY_COUNT))]
+    journey_count: Option<u64>,
+
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_TOTAL_COUNT as i64))]
+    total_companions_met: Option<u32>,
+
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_TOTAL_COUNT as i64))]
+    total_collected_symbols: Option<u32>,
+
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_COLLECTED_SYMBOLS as i64))]
+    collected_symbols: Option<u32>,
+
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_COMPANIONS as i64))]
+    companions_met: Option<u32>,
+
+    /// Sets the last played time, either "now" or an RFC 3339 timestamp
+    #[arg(long, value_parser = parse_last_played)]
+    #[serde(deserialize_with = "deserialize_last_played")]
+    last_played: Option<DateTime<Utc>>,
+
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_SCARF_LENGTH as i64))]
     scarf_length: Option<u32>,
 
     #[arg(long, value_parser = PossibleValuesParser::new(LEVEL_NAMES))]
     current_level: Option<String>,
 
-    #[arg(long, value_parser = value_parser!(u32).range(0..=21))]
+    #[arg(long, value_parser = value_parser!(u32).range(0..=MAX_SYMBOL_ID as i64))]
     symbol: Option<u32>,
 
     #[arg(long, value_parser = PossibleValuesParser::new(["red", "white"]))]
     robe_color: Option<String>,
 
     /// Sets the robe tier from 1 to 4, white robe always has a minimum of 2
-    #[arg(long, value_parser = value_parser!(u32).range(1..=4))]
+    #[arg(
+        long,
+        value_parser = value_parser!(u32).range(MIN_ROBE_TIER as i64..=MAX_ROBE_TIER as i64)
+    )]
     robe_tier: Option<u32>,
+
+    /// Sets a glyph, e.g. "Pink Desert:2=on", glyphs are counted from 1
+    #[arg(long = "glyph", value_name = "LEVEL:NUMBER=on|off", value_parser = parse_toggle)]
+    glyphs: Vec<Toggle>,
+
+    /// Sets a mural, e.g. "Sunken City:1=off", murals are counted from 1
+    #[arg(long = "mural", value_name = "LEVEL:NUMBER=on|off", value_parser = parse_toggle)]
+    murals: Vec<Toggle>,
+
+    /// Appends a companion to the companion lists
+    #[arg(long = "add-companion", value_name = "NAME:STEAM_ID:SYMBOL", value_parser = parse_companion)]
+    add_companions: Vec<NewCompanion>,
+
+    /// Removes a companion by name or by position, counted from 0. Applied before additions
+    #[arg(long = "remove-companion", value_name = "NAME|POSITION")]
+    remove_companions: Vec<String>,
+}
+
+
+/// A glyph or mural position with its state, written as `LEVEL:NUMBER=on|off`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct Toggle {
+    pub level: usize,
+    pub index: usize,
+    pub enabled: bool,
+}
+
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+struct NewCompanion {
+    name: String,
+    steam_id: u32,
+    symbol: u32,
+}
+
+
+enum Outcome {
+    Skipped(String),
+    Edited {
+        changes: Vec<Change>,
+        backup: Option<String>,
+    },
 }
 
 
 pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
-    let in_file = File::open(&sub_args.in_path)?;
+    match &sub_args.recipe {
+        Some(recipe_path) => execute_recipe(sub_args, recipe_path),
+        None => execute_single(sub_args),
+    }
+}
+
+
+fn execute_single(args: &Args) -> Result<()> {
+    let (in_path, out_path) = match (args.paths.as_slice(), args.in_place || args.dry_run) {
+        ([in_path], true) => (in_path, None),
+        ([in_path, out_path], false) => (in_path, Some(out_path)),
+        ([_], false) => bail!("Missing output path, use --in-place to edit the input file"),
+        ([_, _], true) => bail!("--in-place and --dry-run only take the input path"),
+        _ => bail!("Expected an input and an output path, use --recipe to edit many savefiles"),
+    };
+
+    let in_data = fs::read(in_path)?;
+
+    let savefile = Savefile::from_reader(Cursor::new(&in_data))?;
+
+    let new_savefile = args.changes.apply(&savefile, edit_mode(args.consistent))?;
+
+    if args.dry_run {
+        let changes = savefile.diff(&new_savefile);
+
+        if changes.is_empty() {
+            println!("No changes");
+        }
+        for change in changes {
+            println!("{}", change);
+        }
+
+        return Ok(());
+    }
 
-    let savefile = Savefile::from_reader(in_file)?;
+    let store = BackupStore::open()?;
 
-    let new_savefile = edit_file(&savefile, &sub_args)?;
+    let Some(out_path) = out_path else {
+        if let Some(backup) = write_in_place(&store, in_path, &new_savefile, args.steam_cloud)? {
+            println!("Backup saved as {}", backup);
+        }
 
-    let out_file = fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(&sub_args.out_path)?;
+        return Ok(());
+    };
 
-    new_savefile.write(out_file)?;
+    if !args.yes && differs_from(out_path, &in_data) {
+        confirm_overwrite(out_path)?;
+    }
+
+    let written = write_savefile(
+        &store,
+        out_path,
+        &new_savefile,
+        args.steam_cloud,
+        Reason::Edit,
+    )?;
+    for warning in written.warnings {
+        eprintln!("Warning: {}", warning);
+    }
 
     Ok(())
 }
 
 
-fn edit_file(cur_savefile: &Savefile, args: &Args) -> Result<Savefile> {
-    let mut savefile = cur_savefile.clone();
+fn execute_recipe(args: &Args, recipe_path: &Path) -> Result<()> {
+    if !args.in_place && !args.dry_run {
+        bail!("Recipes edit savefiles in place, use --in-place or --dry-run");
+    }
+
+    let recipe = Recipe::from_path(recipe_path)?;
+    let mode = edit_mode(args.consistent || recipe.consistent);
+
+    let paths = expand_paths(&args.paths)?;
+    let store = BackupStore::open()?;
+
+    let (mut edited, mut skipped, mut failed) = (0, 0, 0);
+
+    for path in &paths {
+        match edit_with_recipe(&store, path, &recipe, args, mode) {
+            Ok(Outcome::Skipped(reason)) => {
+                skipped += 1;
+                println!("{}: skipped, {}", path.display(), reason);
+            }
+            Ok(Outcome::Edited { changes, backup }) => {
+                edited += 1;
+                match backup {
+                    Some(backup) => println!(
+                        "{}: {} changes, backup saved as {}",
+                        path.display(),
+                        changes.len(),
+                        backup
+                    ),
+                    None => println!("{}: {} changes", path.display(), changes.len()),
+                }
+                if args.dry_run {
+                    for change in changes {
+                        println!("  {}", change);
+                    }
+                }
+            }
+            Err(err) => {
+                failed += 1;
+                println!("{}: failed, {:#}", path.display(), err);
+            }
+        }
+    }
+
+    println!(
+        "\n{} edited, {} skipped, {} failed",
+        edited, skipped, failed
+    );
+
+    if failed > 0 {
+        bail!("{} of {} savefiles failed", failed, paths.len());
+    }
+
+    Ok(())
+}
+
+
+fn edit_with_recipe(
+    store: &BackupStore,
+    path: &Path,
+    recipe: &Recipe,
+    args: &Args,
+    mode: EditMode,
+) -> Result<Outcome> {
+    let savefile = Savefile::from_path(path)?;
+
+    if let Some(reason) = recipe.when.mismatch(&savefile) {
+        return Ok(Outcome::Skipped(reason));
+    }
+
+    let new_savefile = recipe.set.apply(&savefile, mode)?;
+    let new_savefile = args.changes.apply(&new_savefile, mode)?;
+
+    let changes = savefile.diff(&new_savefile);
+
+    let backup = if args.dry_run || changes.is_empty() {
+        None
+    } else {
+        write_in_place(store, path, &new_savefile, args.steam_cloud)?
+    };
+
+    Ok(Outcome::Edited { changes, backup })
+}
+
+
+/// Expands glob patterns, other paths are taken as they are.
+///
+/// Patterns that match no savefile are skipped with a warning.
+pub(crate) fn expand_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+
+    for path in paths {
+        let pattern = path.to_string_lossy();
+
+        if !pattern.contains(['*', '?', '[']) {
+            expanded.push(path.clone());
+            continue;
+        }
+
+        let matches = glob::glob(&pattern)
+            .with_context(|| format!("Invalid glob pattern {}", pattern))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            eprintln!("Warning: no savefiles match {}, skipping it", pattern);
+            continue;
+        }
+
+        expanded.extend(matches);
+    }
+
+    Ok(expanded)
+}
+
+
+pub(crate) fn edit_mode(consistent: bool) -> EditMode {
+    if consistent {
+        EditMode::Consistent
+    } else {
+        EditMode::Plain
+    }
+}
+
+
+/// Replaces the file at `path` with `savefile`, returns the id of the backup of the replaced file
+pub(crate) fn write_in_place(
+    store: &BackupStore,
+    path: &Path,
+    savefile: &Savefile,
+    steam_cloud: bool,
+) -> Result<Option<String>> {
+    let written = write_savefile(store, path, savefile, steam_cloud, Reason::Edit)?;
+    for warning in written.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    Ok(written.backup)
+}
+
+
+/// What writing a savefile left behind
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Written {
+    /// Short id of the stored version of the replaced file, if there was one
+    pub backup: Option<String>,
+
+    /// Problems with the Steam Cloud metadata
+    pub warnings: Vec<String>,
+}
+
+
+/// Writes a savefile, see [`write_data`]
+pub(crate) fn write_savefile(
+    store: &BackupStore,
+    path: &Path,
+    savefile: &Savefile,
+    steam_cloud: bool,
+    reason: Reason,
+) -> Result<Written> {
+    let mut data = Vec::new();
+    savefile.write(Cursor::new(&mut data))?;
+
+    write_data(store, path, &data, steam_cloud, reason)
+}
+
+
+/// Writes savefile data, optionally keeping the Steam Cloud metadata next to it in sync.
+///
+/// Both the replaced and the written contents are kept in `store`, which is the only backup that
+/// is made.
+pub(crate) fn write_data(
+    store: &BackupStore,
+    path: &Path,
+    data: &[u8],
+    steam_cloud: bool,
+    reason: Reason,
+) -> Result<Written> {
+    let backup = if path.exists() {
+        let snapshot = store
+            .back_up(path, reason)
+            .with_context(|| format!("Failed to back up {}", path.display()))?;
+        Some(snapshot.short_id().to_string())
+    } else {
+        None
+    };
+
+    let (cache, mut warnings) = if steam_cloud {
+        cloud::prepare_save(path)?
+    } else {
+        (None, Vec::new())
+    };
+
+    if steam_cloud && cache.is_none() {
+        warnings.push(format!(
+            "No Steam Cloud metadata found for {}",
+            path.display()
+        ));
+    }
+
+    write_atomic(path, data).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    if let Some(mut cache) = cache {
+        cache.update(path)?;
+    }
+
+    store
+        .snapshot(path, reason)
+        .with_context(|| format!("Failed to back up {}", path.display()))?;
+
+    Ok(Written { backup, warnings })
+}
+
+
+/// Replaces the file at `path` in one step, so an interrupted write or a reader never sees half
+/// a file.
+///
+/// The contents are written to a hidden file next to it first, which is then renamed over it.
+pub(crate) fn write_atomic<C>(path: &Path, contents: C) -> io::Result<()>
+where
+    C: AsRef<[u8]>,
+{
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path).map_err(|err| {
+        let _ = fs::remove_file(&tmp_path);
+        err
+    })
+}
+
 
-    if let Some(val) = args.scarf_length {
-        savefile.scarf_length.set_length(val)?;
+/// Whether `path` exists with contents other than `data`
+pub(crate) fn differs_from(path: &Path, data: &[u8]) -> bool {
+    match fs::read(path) {
+        Ok(existing) => existing != data,
+        Err(_) => path.exists(),
     }
+}
+
 
-    if let Some(val) = &args.current_level {
-        savefile.current_level.set_by_name(&val)?;
+pub(crate) fn confirm_overwrite(path: &Path) -> Result<()> {
+    if !io::stdin().is_terminal() {
+        bail!(
+            "{} already exists with different contents, use --yes to overwrite it",
+            path.display()
+        );
     }
 
-    if let Some(val) = args.symbol {
-        savefile.symbol.set_by_id(val)?;
+    print!(
+        "{} already exists with different contents. Overwrite? [y/N] ",
+        path.display()
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => bail!("Not overwriting {}", path.display()),
     }
+}
+
+
+impl Changes {
+    /// Applies all changes to a copy of `cur_savefile`
+    pub fn apply(&self, cur_savefile: &Savefile, mode: EditMode) -> Result<Savefile> {
+        let mut savefile = cur_savefile.clone();
 
-    if let Some(color) = &args.robe_color {
-        match color.as_ref() {
-            "red" => savefile.robe.set_color(RobeColor::Red),
-            "white" => savefile.robe.set_color(RobeColor::White),
-            _ => (),
+        for companion in &self.remove_companions {
+            let index = find_companion(&savefile, companion)?;
+            savefile.apply(&Edit::RemoveCompanion(index), mode)?;
         }
+
+        for edit in self.edits()? {
+            savefile.apply(&edit, mode)?;
+        }
+
+        Ok(savefile)
     }
 
-    if let Some(tier) = args.robe_tier {
-        savefile.robe.set_tier(tier)?;
+    /// Converts the changes into edits, checking values the flags check on parsing
+    pub fn edits(&self) -> Result<Vec<Edit>> {
+        let mut edits = Vec::new();
+
+        if let Some(val) = self.journey_count {
+            let val = check_range("journey_count", val, 0..=MAX_JOURNEY_COUNT)?;
+            edits.push(Edit::JourneyCount(val));
+        }
+
+        if let Some(val) = self.total_companions_met {
+            let val = check_range("total_companions_met", val, 0..=MAX_TOTAL_COUNT)?;
+            edits.push(Edit::TotalCompanionsMet(val));
+        }
+
+        if let Some(val) = self.total_collected_symbols {
+            let val = check_range("total_collected_symbols", val, 0..=MAX_TOTAL_COUNT)?;
+            edits.push(Edit::TotalCollectedSymbols(val));
+        }
+
+        if let Some(val) = self.collected_symbols {
+            let val = check_range("collected_symbols", val, 0..=MAX_COLLECTED_SYMBOLS)?;
+            edits.push(Edit::CollectedSymbols(val));
+        }
+
+        if let Some(val) = self.last_played {
+            edits.push(Edit::LastPlayed(val));
+        }
+
+        if let Some(val) = self.scarf_length {
+            let val = check_range("scarf_length", val, 0..=MAX_SCARF_LENGTH)?;
+            edits.push(Edit::ScarfLength(val));
+        }
+
+        if let Some(val) = &self.current_level {
+            let id = level_id(val)?;
+            edits.push(Edit::CurrentLevel(id as u64));
+        }
+
+        if let Some(val) = self.symbol {
+            let val = check_range("symbol", val, 0..=MAX_SYMBOL_ID)?;
+            edits.push(Edit::Symbol(val));
+        }
+
+        if let Some(color) = &self.robe_color {
+            edits.push(Edit::RobeColor(color.parse::<RobeColor>()?));
+        }
+
+        if let Some(tier) = self.robe_tier {
+            let tier = check_range("robe_tier", tier, MIN_ROBE_TIER..=MAX_ROBE_TIER)?;
+            edits.push(Edit::RobeTier(tier));
+        }
+
+        for glyph in &self.glyphs {
+            edits.push(Edit::Glyph {
+                level: glyph.level,
+                index: glyph.index,
+                collected: glyph.enabled,
+            });
+        }
+
+        for mural in &self.murals {
+            edits.push(Edit::Mural {
+                level: mural.level,
+                index: mural.index,
+                found: mural.enabled,
+            });
+        }
+
+        for companion in &self.add_companions {
+            edits.push(Edit::AddCompanion {
+                name: companion.name.clone(),
+                steam_id: companion.steam_id,
+                symbol: companion.symbol,
+            });
+        }
+
+        // companions_met is applied last, so it can refer to added companions
+        if let Some(val) = self.companions_met {
+            let val = check_range("companions_met", val, 0..=MAX_COMPANIONS as u32)?;
+            edits.push(Edit::CompanionsMet(val));
+        }
+
+        Ok(edits)
     }
+}
+
+
+pub(crate) fn check_range<T>(field: &str, value: T, range: RangeInclusive<T>) -> Result<T>
+where
+    T: PartialOrd + fmt::Display,
+{
+    if !range.contains(&value) {
+        bail!(
+            "{} must be between {} and {}, got {}",
+            field,
+            range.start(),
+            range.end(),
+            value
+        );
+    }
+
+    Ok(value)
+}
+
 
-    Ok(savefile)
+pub(crate) fn find_companion(savefile: &Savefile, name_or_position: &str) -> Result<usize> {
+    if let Some(index) = savefile
+        .companions
+        .iter()
+        .position(|companion| companion.name == name_or_position)
+    {
+        return Ok(index);
+    }
+
+    match name_or_position.parse::<usize>() {
+        Ok(index) if index < savefile.companions.count() => Ok(index),
+        _ => bail!("No companion named or at position {}", name_or_position),
+    }
+}
+
+
+pub(crate) fn level_id(name: &str) -> Result<usize> {
+    LEVEL_NAMES
+        .iter()
+        .position(|&level| level == name)
+        .with_context(|| format!("Unknown level {}", name))
+}
+
+
+fn parse_last_played(value: &str) -> Result<DateTime<Utc>, String> {
+    if value == "now" {
+        return Ok(Utc::now());
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|err| err.to_string())
+}
+
+
+fn deserialize_last_played<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse_last_played(&value).map_err(de::Error::custom))
+        .transpose()
+}
+
+
+impl TryFrom<String> for Toggle {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        parse_toggle(&value)
+    }
+}
+
+impl TryFrom<String> for NewCompanion {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        parse_companion(&value)
+    }
+}
+
+
+fn parse_toggle(value: &str) -> Result<Toggle, String> {
+    let (position, enabled) = value
+        .rsplit_once('=')
+        .ok_or("expected LEVEL:NUMBER=on|off")?;
+    let (level, number) = position
+        .rsplit_once(':')
+        .ok_or("expected LEVEL:NUMBER=on|off")?;
+
+    let level = level_id(level).map_err(|err| err.to_string())?;
+
+    let index = match number.parse::<usize>() {
+        Ok(number) if number > 0 => number - 1,
+        _ => return Err(format!("invalid number {}, counting starts at 1", number)),
+    };
+
+    let enabled = match enabled {
+        "on" | "true" | "1" => true,
+        "off" | "false" | "0" => false,
+        _ => return Err(format!("invalid value {}, expected on or off", enabled)),
+    };
+
+    Ok(Toggle {
+        level,
+        index,
+        enabled,
+    })
+}
+
+
+fn parse_companion(value: &str) -> Result<NewCompanion, String> {
+    let mut parts = value.rsplitn(3, ':');
+
+    let (Some(symbol), Some(steam_id), Some(name)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err("expected NAME:STEAM_ID:SYMBOL".to_string());
+    };
+
+    let steam_id = match steam_id.parse::<u32>() {
+        Ok(steam_id) if steam_id != 0 => steam_id,
+        _ => return Err(format!("invalid steam id {}", steam_id)),
+    };
+
+    let symbol = match symbol.parse::<u32>() {
+        Ok(symbol) if symbol <= MAX_COMPANION_SYMBOL_ID => symbol,
+        _ => {
+            return Err(format!(
+                "invalid symbol {}, expected 0 to {}",
+                symbol, MAX_COMPANION_SYMBOL_ID
+            ))
+        }
+    };
+
+    Ok(NewCompanion {
+        name: name.to_string(),
+        steam_id,
+        symbol,
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glyph_toggle() {
+        let toggle = parse_toggle("Pink Desert:2=on").unwrap();
+        assert_eq!(LEVEL_NAMES[toggle.level], "Pink Desert");
+        assert_eq!(toggle.index, 1);
+        assert!(toggle.enabled);
+
+        assert!(parse_toggle("Pink Desert:0=on").is_err());
+        assert!(parse_toggle("Pink Desert:1=maybe").is_err());
+        assert!(parse_toggle("Nowhere:1=off").is_err());
+    }
+
+    #[test]
+    fn replace_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("SAVE.BIN");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn skip_unmatched_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("SAVE.BIN");
+        fs::write(&path, b"").unwrap();
+
+        let paths = expand_paths(&[dir.path().join("*.BIN"), dir.path().join("*.none")]).unwrap();
+
+        assert_eq!(paths, vec![path]);
+    }
 }
@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use tracing::debug;
+
+use crate::backup::hash;
+use crate::edit::expand_paths;
+use crate::info::{CompanionLists, LevelStatus, SaveInfo, Section};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefiles to export, glob patterns are expanded
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    #[arg(
+        long,
+        short,
+        default_value = "csv",
+        value_parser = PossibleValuesParser::new(["csv", "sqlite"])
+    )]
+    format: String,
+
+    /// Directory for the CSV files, or the SQLite database to add the savefiles to
+    #[arg(long, short)]
+    output: PathBuf,
+}
+
+
+/// Rows of one table, every row starts with the hash of the savefile it came from
+#[derive(Debug, Clone, PartialEq)]
+struct Table {
+    name: &'static str,
+    columns: &'static [(&'static str, &'static str)],
+    primary_key: &'static [&'static str],
+    rows: Vec<Vec<Value>>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let mut savefiles = Vec::new();
+    let mut hashes = HashSet::new();
+
+    for path in expand_paths(&sub_args.paths)? {
+        let data = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let file_hash = hash(&data);
+
+        if !hashes.insert(file_hash.clone()) {
+            debug!(
+                "Skipping {}, same content as an earlier savefile",
+                path.display()
+            );
+            continue;
+        }
+
+        let mut savefile = Savefile::from_reader(Cursor::new(data))?;
+        savefile.path = path;
+        savefiles.push((file_hash, savefile));
+    }
+
+    let tables = tables(&savefiles);
+
+    match sub_args.format.as_ref() {
+        "sqlite" => write_sqlite(&sub_args.output, &tables)?,
+        _ => write_csv(&sub_args.output, &tables)?,
+    }
+
+    println!(
+        "Exported {} savefiles to {}",
+        savefiles.len(),
+        sub_args.output.display()
+    );
+
+    Ok(())
+}
+
+
+fn tables(savefiles: &[(String, Savefile)]) -> Vec<Table> {
+    let mut stats = Table {
+        name: "stats",
+        columns: &[
+            ("file_hash", "TEXT NOT NULL"),
+            ("path", "TEXT NOT NULL"),
+            ("platform", "TEXT NOT NULL"),
+            ("journey_count", "INTEGER NOT NULL"),
+            ("total_companions_met", "INTEGER NOT NULL"),
+            ("total_collected_symbols", "INTEGER NOT NULL"),
+            ("collected_symbols", "INTEGER NOT NULL"),
+            ("current_level", "TEXT NOT NULL"),
+            ("companions_met", "INTEGER NOT NULL"),
+            ("scarf_length", "INTEGER NOT NULL"),
+            ("symbol", "INTEGER NOT NULL"),
+            ("robe_color", "TEXT NOT NULL"),
+            ("robe_tier", "INTEGER NOT NULL"),
+            ("last_played", "TEXT NOT NULL"),
+        ],
+        primary_key: &["file_hash"],
+        rows: Vec::new(),
+    };
+
+    let mut glyphs = Table {
+        name: "glyphs",
+        columns: &[
+            ("file_hash", "TEXT NOT NULL"),
+            ("level", "TEXT NOT NULL"),
+            ("number", "INTEGER NOT NULL"),
+            ("collected", "INTEGER NOT NULL"),
+        ],
+        primary_key: &["file_hash", "level", "number"],
+        rows: Vec::new(),
+    };
+
+    let mut murals = Table {
+        name: "murals",
+        columns: &[
+            ("file_hash", "TEXT NOT NULL"),
+            ("level", "TEXT NOT NULL"),
+            ("number", "INTEGER NOT NULL"),
+            ("found", "INTEGER NOT NULL"),
+        ],
+        primary_key: &["file_hash", "level", "number"],
+        rows: Vec::new(),
+    };
+
+    let mut companions = Table {
+        name: "companions",
+        columns: &[
+            ("file_hash", "TEXT NOT NULL"),
+            ("position", "INTEGER NOT NULL"),
+            ("name", "TEXT NOT NULL"),
+            ("status", "TEXT NOT NULL"),
+            ("steam_id", "INTEGER NOT NULL"),
+            ("symbol", "INTEGER"),
+        ],
+        primary_key: &["file_hash", "position"],
+        rows: Vec::new(),
+    };
+
+    let int = |value: u64| Value::Integer(value as i64);
+
+    for (file_hash, savefile) in savefiles {
+        let info = SaveInfo::new(savefile, &Section::ALL);
+        let hash = || Value::Text(file_hash.clone());
+
+        if let Some(stat) = &info.stats {
+            stats.rows.push(vec![
+                hash(),
+                Value::Text(info.path.clone()),
+                Value::Text(info.platform.clone()),
+                int(stat.journey_count),
+                int(stat.total_companions_met.into()),
+                int(stat.total_collected_symbols.into()),
+                int(stat.collected_symbols.into()),
+                Value::Text(stat.current_level.clone()),
+                int(stat.companions_met.into()),
+                int(stat.scarf_length.into()),
+                int(stat.symbol.into()),
+                Value::Text(stat.robe_color.clone()),
+                int(stat.robe_tier.into()),
+                Value::Text(stat.last_played.clone()),
+            ]);
+        }
+
+        let level_rows = |table: &mut Table, levels: Vec<LevelStatus>| {
+            for level in levels {
+                for (index, found) in level.found.into_iter().enumerate() {
+                    table.rows.push(vec![
+                        hash(),
+                        Value::Text(level.level.clone()),
+                        int(index as u64 + 1),
+                        int(found.into()),
+                    ]);
+                }
+            }
+        };
+
+        level_rows(&mut glyphs, info.glyphs.unwrap_or_default());
+        level_rows(&mut murals, info.murals.unwrap_or_default());
+
+        let lists = info
+            .companions
+            .unwrap_or_else(|| CompanionLists::new(savefile));
+        let listed = lists
+            .current
+            .iter()
+            .map(|companion| (companion, "current"))
+            .chain(lists.past.iter().map(|companion| (companion, "past")));
+
+        for (position, (companion, status)) in listed.enumerate() {
+            companions.rows.push(vec![
+                hash(),
+                int(position as u64 + 1),
+                Value::Text(companion.name.clone()),
+                Value::Text(status.to_string()),
+                int(companion.steam_id.into()),
+                companion
+                    .symbol
+                    .map_or(Value::Null, |symbol| int(symbol.into())),
+            ]);
+        }
+    }
+
+    vec![stats, glyphs, murals, companions]
+}
+
+
+/// Writes one `<table>.csv` per table into `dir`, replacing earlier exports
+fn write_csv(dir: &Path, tables: &[Table]) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    for table in tables {
+        let path = dir.join(format!("{}.csv", table.name));
+        fs::write(&path, csv(table))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+
+fn csv(table: &Table) -> String {
+    let mut out = String::new();
+
+    let header = table
+        .columns
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+
+    for row in &table.rows {
+        let fields = row
+            .iter()
+            .map(|value| match value {
+                Value::Null => String::new(),
+                Value::Integer(value) => value.to_string(),
+                Value::Real(value) => value.to_string(),
+                Value::Text(text) if text.contains([',', '"', '\r', '\n']) => {
+                    format!("\"{}\"", text.replace('"', "\"\""))
+                }
+                Value::Text(text) => text.clone(),
+                Value::Blob(_) => unreachable!("exports contain no blobs"),
+            })
+            .collect::<Vec<_>>();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+
+    out
+}
+
+
+/// Adds the tables to the database at `path`, savefiles exported before are replaced by hash
+fn write_sqlite(path: &Path, tables: &[Table]) -> Result<()> {
+    let mut connection =
+        Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let transaction = connection.transaction()?;
+
+    for table in tables {
+        let columns = table
+            .columns
+            .iter()
+            .map(|(name, kind)| format!("{} {}", name, kind))
+            .collect::<Vec<_>>();
+        transaction.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} ({}, PRIMARY KEY ({}))",
+                table.name,
+                columns.join(", "),
+                table.primary_key.join(", ")
+            ),
+            [],
+        )?;
+
+        let mut delete =
+            transaction.prepare(&format!("DELETE FROM {} WHERE file_hash = ?1", table.name))?;
+        let mut insert = transaction.prepare(&format!(
+            "INSERT INTO {} VALUES ({})",
+            table.name,
+            vec!["?"; table.columns.len()].join(", ")
+        ))?;
+
+        let mut cleared = Vec::new();
+        for row in &table.rows {
+            if !cleared.contains(&&row[0]) {
+                delete.execute([&row[0]])?;
+                cleared.push(&row[0]);
+            }
+            insert.execute(params_from_iter(row))?;
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use jrny_save::{Edit, EditMode};
+
+    use super::*;
+    use crate::test_util::{test_savefile, TEST_FILE};
+
+    fn savefiles() -> Vec<(String, Savefile)> {
+        let savefile = test_savefile();
+        let (edited, _) = savefile
+            .preview(&Edit::JourneyCount(22), EditMode::Plain)
+            .unwrap();
+
+        vec![(hash(TEST_FILE), savefile), ("edited".to_string(), edited)]
+    }
+
+    #[test]
+    fn csv_tables() {
+        let tables = tables(&savefiles());
+        let files = tables.iter().map(csv).collect::<Vec<_>>();
+
+        let stats = files[0].lines().collect::<Vec<_>>();
+        assert_eq!(stats.len(), 3);
+        assert!(stats[0].starts_with("file_hash,path,platform,journey_count,"));
+        assert!(stats[1].starts_with(&format!(
+            "{},,PC,21,21,107,21,Broken Bridge,",
+            hash(TEST_FILE)
+        )));
+        assert!(stats[2].starts_with("edited,,PC,22,"));
+
+        assert_eq!(files[1].lines().count(), 1 + 2 * 21);
+        assert!(files[1].contains("\r\nedited,Pink Desert,4,1\r\n"));
+        assert!(files[2].contains("\r\nedited,Snow,2,0\r\n"));
+        assert!(files[3].contains("\r\nedited,1,Wanderer,current,1567713042,"));
+        assert!(files[3].contains("\r\nedited,8,Ferris,past,596714625,"));
+
+        let quoted = Table {
+            rows: vec![vec![Value::Text("a,\"b\"".to_string()), Value::Null]],
+            ..tables[0].clone()
+        };
+        assert!(csv(&quoted).ends_with("\r\n\"a,\"\"b\"\"\",\r\n"));
+    }
+
+    #[test]
+    fn sqlite_keyed_by_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saves.db");
+        let savefiles = savefiles();
+
+        write_sqlite(&path, &tables(&savefiles[..1])).unwrap();
+        write_sqlite(&path, &tables(&savefiles)).unwrap();
+
+        let connection = Connection::open(&path).unwrap();
+        let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+
+        assert_eq!(count("SELECT COUNT(*) FROM stats"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM glyphs"), 42);
+        assert_eq!(count("SELECT COUNT(*) FROM murals"), 20);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM companions WHERE status = 'past'"),
+            4
+        );
+        assert_eq!(
+            count(
+                "SELECT SUM(collected) FROM glyphs JOIN stats USING (file_hash) \
+                 WHERE journey_count = 22"
+            ),
+            13
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use jrny_save::Savefile;
+use serde::{Deserialize, Serialize};
+
+use crate::backup::hash;
+use crate::DIRS;
+
+
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+
+/// Progress of a savefile at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub recorded: DateTime<Utc>,
+    pub path: PathBuf,
+    pub hash: String,
+    pub journey_count: u64,
+    pub glyphs_found: u32,
+    pub murals_found: u32,
+    pub total_companions_met: u32,
+    pub collected_symbols: u32,
+    pub current_level: String,
+    pub robe_color: String,
+    pub robe_tier: u32,
+}
+
+
+/// Records written without a gap longer than the session gap in between
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Session<'a> {
+    /// Last record before the session, progress is counted from here
+    pub before: Option<&'a Record>,
+    pub records: &'a [Record],
+}
+
+
+/// Every distinct version of the savefiles wayfarer has seen, stored as JSON lines
+#[derive(Debug, Clone)]
+pub(crate) struct History {
+    path: PathBuf,
+}
+
+
+impl History {
+    pub fn open() -> Result<Self> {
+        let dir = DIRS.data_local_dir();
+        fs::create_dir_all(dir)?;
+
+        Ok(Self::at(dir.join(HISTORY_FILE_NAME)))
+    }
+
+    pub fn at<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self { path: path.into() }
+    }
+
+    /// Records the savefile at `path` unless it didn't change since the last record
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn record(&self, path: &Path) -> Result<Option<Record>> {
+        let path = fs::canonicalize(path)?;
+        let data = fs::read(&path)?;
+        let savefile = Savefile::from_path(&path)?;
+
+        let record = Record::new(&savefile, &data, path);
+
+        let latest = self.records_of(&record.path)?.pop();
+        if latest.is_some_and(|latest| latest.hash == record.hash) {
+            return Ok(None);
+        }
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+
+        Ok(Some(record))
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> Result<Vec<Record>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Corrupt history"))
+            .collect()
+    }
+
+    /// Records of the savefile at `path`, oldest first
+    pub fn records_of(&self, path: &Path) -> Result<Vec<Record>> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+
+        let mut records = self.records()?;
+        records.retain(|record| record.path == path);
+
+        Ok(records)
+    }
+}
+
+
+impl Record {
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn new(savefile: &Savefile, data: &[u8], path: PathBuf) -> Self {
+        Self {
+            recorded: Utc::now(),
+            path,
+            hash: hash(data),
+            journey_count: savefile.journey_count,
+            glyphs_found: count_found(savefile.glyphs.all()),
+            murals_found: count_found(savefile.murals.all()),
+            total_companions_met: savefile.total_companions_met,
+            collected_symbols: savefile.collected_symbols,
+            current_level: savefile.current_level.to_string(),
+            robe_color: savefile.robe.color().to_string(),
+            robe_tier: savefile.robe.tier(),
+        }
+    }
+}
+
+
+impl Session<'_> {
+    pub fn start(&self) -> DateTime<Utc> {
+        self.records[0].recorded
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.records[self.records.len() - 1].recorded
+    }
+
+    pub fn first(&self) -> &Record {
+        self.before.unwrap_or(&self.records[0])
+    }
+
+    pub fn last(&self) -> &Record {
+        &self.records[self.records.len() - 1]
+    }
+}
+
+
+#[cfg_attr(not(feature = "watch"), allow(dead_code))]
+fn count_found<I>(levels: I) -> u32
+where
+    I: Iterator<Item = (usize, Vec<bool>)>,
+{
+    levels
+        .flat_map(|(_, found)| found)
+        .filter(|&found| found)
+        .count() as u32
+}
+
+
+/// Splits the records of one savefile into sessions
+pub(crate) fn sessions(records: &[Record], gap: Duration) -> Vec<Session<'_>> {
+    let mut sessions = Vec::new();
+    let mut start = 0_usize;
+
+    for idx in 1..=records.len() {
+        let split = idx == records.len() || records[idx].recorded - records[idx - 1].recorded > gap;
+        if !split {
+            continue;
+        }
+
+        sessions.push(Session {
+            before: start.checked_sub(1).map(|before| &records[before]),
+            records: &records[start..idx],
+        });
+        start = idx;
+    }
+
+    sessions
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{temp_savefile, test_savefile, TEST_FILE};
+
+    fn record_at(minutes: i64, journey_count: u64) -> Record {
+        let savefile = test_savefile();
+        let mut record = Record::new(&savefile, TEST_FILE, PathBuf::from("SAVE.BIN"));
+        record.recorded = DateTime::<Utc>::MIN_UTC + Duration::minutes(minutes);
+        record.journey_count = journey_count;
+        record
+    }
+
+    #[test]
+    fn records_distinct_versions() {
+        let (dir, path) = temp_savefile();
+        let history = History::at(dir.path().join("history.jsonl"));
+
+        let first = history.record(&path).unwrap().unwrap();
+        assert!(history.record(&path).unwrap().is_none());
+
+        let mut savefile = Savefile::from_path(&path).unwrap();
+        savefile.journey_count += 1;
+        savefile.write(fs::File::create(&path).unwrap()).unwrap();
+        history.record(&path).unwrap().unwrap();
+
+        let records = history.records_of(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], first);
+        assert_eq!(records[1].journey_count, 22);
+        assert_eq!(first.current_level, "Broken Bridge");
+    }
+
+    #[test]
+    fn splits_sessions() {
+        let records = [
+            record_at(0, 1),
+            record_at(20, 2),
+            record_at(200, 3),
+            record_at(210, 4),
+            record_at(500, 4),
+        ];
+
+        let sessions = sessions(&records, Duration::minutes(60));
+
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].records.len(), 2);
+        assert_eq!(sessions[0].first().journey_count, 1);
+        assert_eq!(sessions[1].first().journey_count, 2);
+        assert_eq!(sessions[1].last().journey_count, 4);
+        assert_eq!(sessions[2].records.len(), 1);
+    }
+}
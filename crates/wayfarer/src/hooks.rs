@@ -0,0 +1,401 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{fs, thread};
+
+use anyhow::{bail, Context, Result};
+use jrny_save::{Savefile, LEVEL_NAMES};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::DIRS;
+
+
+const HOOKS_FILE_NAME: &str = "hooks.toml";
+
+
+/// Commands to run when progress changes, read from a TOML file, e.g.
+///
+/// ```toml
+/// [[hook]]
+/// on = ["glyph_collected", "mural_found"]
+/// command = ["notify-send", "Journey", "Found something"]
+///
+/// [[hook]]
+/// command = ["./log-progress.sh"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    #[serde(rename = "hook")]
+    hooks: Vec<Hook>,
+}
+
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Hook {
+    /// Names of the events to run on, all events if empty
+    #[serde(default)]
+    on: Vec<String>,
+
+    /// Program and arguments
+    command: Vec<String>,
+}
+
+
+/// Progress made between two versions of a savefile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    GlyphCollected {
+        level: String,
+        number: usize,
+    },
+    MuralFound {
+        level: String,
+        number: usize,
+    },
+    JourneyCompleted {
+        journey_count: u64,
+    },
+    CompanionMet {
+        name: String,
+        steam_id: u32,
+    },
+    RobeUpgraded {
+        color: String,
+        old_tier: u32,
+        new_tier: u32,
+    },
+    LevelChanged {
+        old_level: String,
+        new_level: String,
+    },
+}
+
+
+impl Hooks {
+    /// Default location of the hooks file
+    pub fn path() -> PathBuf {
+        DIRS.config_dir().join(HOOKS_FILE_NAME)
+    }
+
+    /// Reads the hooks at `path`, or the default hooks file if it exists
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::from_path(path),
+            None if Self::path().exists() => Self::from_path(&Self::path()),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read hooks {}", path.display()))?;
+        let hooks = toml::from_str::<Self>(&content)
+            .with_context(|| format!("Invalid hooks {}", path.display()))?;
+
+        for hook in &hooks.hooks {
+            if hook.command.is_empty() {
+                bail!("Empty hook command in {}", path.display());
+            }
+            if let Some(name) = hook
+                .on
+                .iter()
+                .find(|name| !Event::NAMES.contains(&name.as_str()))
+            {
+                bail!(
+                    "Unknown event {} in {}, expected one of {}",
+                    name,
+                    path.display(),
+                    Event::NAMES.join(", ")
+                );
+            }
+        }
+
+        Ok(hooks)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Runs the hooks for the progress between `old` and `new` in the background
+    pub fn fire(&self, old: &Savefile, new: &Savefile) {
+        if self.is_empty() {
+            return;
+        }
+
+        for event in Event::between(old, new) {
+            debug!("Firing {:?}", event);
+
+            for hook in self.hooks.iter().filter(|hook| hook.runs_on(&event)) {
+                let hook = hook.clone();
+                let details = event.details(&new.path);
+                thread::spawn(move || {
+                    if let Err(err) = hook.run(&details) {
+                        warn!("Hook {:?} failed: {:#}", hook.command, err);
+                    }
+                });
+            }
+        }
+    }
+}
+
+
+impl Hook {
+    fn runs_on(&self, event: &Event) -> bool {
+        self.on.is_empty() || self.on.iter().any(|name| name == event.name())
+    }
+
+    /// Passes the event as JSON on stdin and as `WAYFARER_*` environment variables
+    fn run(&self, details: &serde_json::Map<String, serde_json::Value>) -> Result<()> {
+        let mut command = Command::new(&self.command[0]);
+        command
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        for (key, value) in details {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            command.env(format!("WAYFARER_{}", key.to_uppercase()), value);
+        }
+
+        let mut child = command.spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // hooks that don't read stdin may close it early
+            let _ = writeln!(stdin, "{}", serde_json::Value::Object(details.clone()));
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "{}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+
+impl Event {
+    pub const NAMES: [&'static str; 6] = [
+        "glyph_collected",
+        "mural_found",
+        "journey_completed",
+        "companion_met",
+        "robe_upgraded",
+        "level_changed",
+    ];
+
+    /// Events for every change from `old` to `new` that counts as progress
+    pub fn between(old: &Savefile, new: &Savefile) -> Vec<Self> {
+        let mut events = Vec::new();
+
+        let found = |old: Vec<(usize, Vec<bool>)>, new: Vec<(usize, Vec<bool>)>| {
+            new.into_iter()
+                .zip(old)
+                .flat_map(|((level, new), (_, old))| {
+                    new.into_iter()
+                        .zip(old)
+                        .enumerate()
+                        .filter(|(_, (new, old))| *new && !old)
+                        .map(move |(idx, _)| (LEVEL_NAMES[level].to_string(), idx + 1))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for (level, number) in found(old.glyphs.all().collect(), new.glyphs.all().collect()) {
+            events.push(Self::GlyphCollected { level, number });
+        }
+
+        for (level, number) in found(old.murals.all().collect(), new.murals.all().collect()) {
+            events.push(Self::MuralFound { level, number });
+        }
+
+        if new.journey_count > old.journey_count {
+            events.push(Self::JourneyCompleted {
+                journey_count: new.journey_count,
+            });
+        }
+
+        for companion in new.companions.iter() {
+            let known = old
+                .companions
+                .iter()
+                .any(|old| old.steam_id == companion.steam_id);
+
+            if !known {
+                events.push(Self::CompanionMet {
+                    name: companion.name.to_string(),
+                    steam_id: companion.steam_id,
+                });
+            }
+        }
+
+        // robes are ordered by progression, a white robe of a lower tier is still an upgrade
+        if new.robe > old.robe {
+            events.push(Self::RobeUpgraded {
+                color: new.robe.color().to_string(),
+                old_tier: old.robe.tier(),
+                new_tier: new.robe.tier(),
+            });
+        }
+
+        if new.current_level.as_ref() != old.current_level.as_ref() {
+            events.push(Self::LevelChanged {
+                old_level: old.current_level.to_string(),
+                new_level: new.current_level.to_string(),
+            });
+        }
+
+        events
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GlyphCollected { .. } => Self::NAMES[0],
+            Self::MuralFound { .. } => Self::NAMES[1],
+            Self::JourneyCompleted { .. } => Self::NAMES[2],
+            Self::CompanionMet { .. } => Self::NAMES[3],
+            Self::RobeUpgraded { .. } => Self::NAMES[4],
+            Self::LevelChanged { .. } => Self::NAMES[5],
+        }
+    }
+
+    /// Fields of the event along with its name and the savefile path
+    fn details(&self, savefile: &Path) -> serde_json::Map<String, serde_json::Value> {
+        let mut details = match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(details)) => details,
+            _ => serde_json::Map::new(),
+        };
+
+        details.insert(
+            "savefile".to_string(),
+            savefile.display().to_string().into(),
+        );
+
+        details
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use jrny_save::{Edit, EditMode, RobeColor};
+
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    #[test]
+    fn progress_events() {
+        let old = test_savefile();
+        assert!(Event::between(&old, &old).is_empty());
+
+        let (level, found) = old
+            .glyphs
+            .all()
+            .find(|(_, found)| found.contains(&false))
+            .unwrap();
+        let index = found.iter().position(|found| !found).unwrap();
+
+        let mut new = old.clone();
+        for edit in [
+            Edit::Glyph {
+                level,
+                index,
+                collected: true,
+            },
+            Edit::JourneyCount(old.journey_count + 1),
+            Edit::AddCompanion {
+                name: "Traveler".to_string(),
+                steam_id: 12345,
+                symbol: 3,
+            },
+        ] {
+            new.apply(&edit, EditMode::Plain).unwrap();
+        }
+
+        assert_eq!(Event::between(&old, &new), vec![
+            Event::GlyphCollected {
+                level: LEVEL_NAMES[level].to_string(),
+                number: index + 1,
+            },
+            Event::JourneyCompleted { journey_count: 22 },
+            Event::CompanionMet {
+                name: "Traveler".to_string(),
+                steam_id: 12345,
+            },
+        ]);
+
+        // going back is not progress
+        assert!(Event::between(&new, &old).is_empty());
+
+        let robe = |color, tier| {
+            let mut savefile = old.clone();
+            savefile
+                .apply(&Edit::RobeColor(color), EditMode::Plain)
+                .unwrap();
+            savefile
+                .apply(&Edit::RobeTier(tier), EditMode::Plain)
+                .unwrap();
+            savefile
+        };
+        let red_4 = robe(RobeColor::Red, 4);
+        let white_2 = robe(RobeColor::White, 2);
+
+        assert_eq!(Event::between(&red_4, &white_2), vec![
+            Event::RobeUpgraded {
+                color: "White".to_string(),
+                old_tier: 4,
+                new_tier: 2,
+            }
+        ]);
+        assert!(Event::between(&white_2, &robe(RobeColor::Red, 3)).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+
+        let hook = Hook {
+            on: vec!["journey_completed".to_string()],
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                format!(
+                    "echo $WAYFARER_EVENT $WAYFARER_JOURNEY_COUNT > {0}; cat >> {0}",
+                    out.display()
+                ),
+            ],
+        };
+
+        let event = Event::JourneyCompleted { journey_count: 22 };
+        assert!(hook.runs_on(&event));
+        assert!(!hook.runs_on(&Event::CompanionMet {
+            name: "Traveler".to_string(),
+            steam_id: 12345
+        }));
+
+        hook.run(&event.details(Path::new("SAVE.BIN"))).unwrap();
+
+        let output = fs::read_to_string(out).unwrap();
+        let (env, stdin) = output.split_once('\n').unwrap();
+        assert_eq!(env, "journey_completed 22");
+
+        let json: serde_json::Value = serde_json::from_str(stdin).unwrap();
+        assert_eq!(json["event"], "journey_completed");
+        assert_eq!(json["savefile"], "SAVE.BIN");
+    }
+}
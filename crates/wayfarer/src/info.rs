@@ -0,0 +1,249 @@
+use jrny_save::{Savefile, LEVEL_NAMES};
+use serde::Serialize;
+
+
+/// Version of the serialized savefile info, bumped on incompatible changes
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+
+/// Serializable view of everything the TUI shows about a savefile
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SaveInfo {
+    pub schema_version: u32,
+    pub path: String,
+    pub platform: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<SymbolArt>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glyphs: Option<Vec<LevelStatus>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub murals: Option<Vec<LevelStatus>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub companions: Option<CompanionLists>,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Stats {
+    pub journey_count: u64,
+    pub total_companions_met: u32,
+    pub total_collected_symbols: u32,
+    pub collected_symbols: u32,
+    pub current_level: String,
+    pub companions_met: u32,
+    pub scarf_length: u32,
+    pub symbol: u32,
+    pub robe_color: String,
+    pub robe_tier: u32,
+    pub last_played: String,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SymbolArt {
+    pub id: u32,
+    pub lines: Vec<String>,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LevelStatus {
+    pub level: String,
+    pub found: Vec<bool>,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct CompanionLists {
+    pub current: Vec<Companion>,
+    pub past: Vec<Companion>,
+}
+
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Companion {
+    pub name: String,
+    pub steam_id: u32,
+    pub steam_id_v3: String,
+    pub steam_url: String,
+    pub symbol: Option<u32>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Section {
+    Stats,
+    Symbol,
+    Glyphs,
+    Murals,
+    Companions,
+}
+
+impl Section {
+    pub const ALL: [Self; 5] = [
+        Self::Stats,
+        Self::Symbol,
+        Self::Glyphs,
+        Self::Murals,
+        Self::Companions,
+    ];
+    pub const NAMES: [&'static str; 5] = ["stats", "symbol", "glyphs", "murals", "companions"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|&n| n == name)
+            .map(|idx| Self::ALL[idx])
+    }
+}
+
+
+impl SaveInfo {
+    pub fn new(savefile: &Savefile, sections: &[Section]) -> Self {
+        let has = |section| sections.contains(&section);
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            path: savefile.path.display().to_string(),
+            platform: savefile.platform.to_string(),
+            stats: has(Section::Stats).then(|| Stats::new(savefile)),
+            symbol: has(Section::Symbol).then(|| SymbolArt::new(savefile)),
+            glyphs: has(Section::Glyphs).then(|| LevelStatus::glyphs(savefile)),
+            murals: has(Section::Murals).then(|| LevelStatus::murals(savefile)),
+            companions: has(Section::Companions).then(|| CompanionLists::new(savefile)),
+        }
+    }
+}
+
+impl Stats {
+    fn new(savefile: &Savefile) -> Self {
+        Self {
+            journey_count: savefile.journey_count,
+            total_companions_met: savefile.total_companions_met,
+            total_collected_symbols: savefile.total_collected_symbols,
+            collected_symbols: savefile.collected_symbols,
+            current_level: savefile.current_level.to_string(),
+            companions_met: savefile.companions_met,
+            scarf_length: *savefile.scarf_length.as_ref(),
+            symbol: *savefile.symbol.as_ref(),
+            robe_color: savefile.robe.color().to_string(),
+            robe_tier: savefile.robe.tier(),
+            last_played: savefile.last_played.to_rfc3339(),
+        }
+    }
+}
+
+impl SymbolArt {
+    fn new(savefile: &Savefile) -> Self {
+        Self {
+            id: *savefile.symbol.as_ref(),
+            lines: savefile
+                .symbol
+                .to_string()
+                .lines()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+impl LevelStatus {
+    fn glyphs(savefile: &Savefile) -> Vec<Self> {
+        savefile
+            .glyphs
+            .all()
+            .map(|(level, found)| Self {
+                level: LEVEL_NAMES[level].to_string(),
+                found,
+            })
+            .collect()
+    }
+
+    fn murals(savefile: &Savefile) -> Vec<Self> {
+        savefile
+            .murals
+            .all()
+            .map(|(level, found)| Self {
+                level: LEVEL_NAMES[level].to_string(),
+                found,
+            })
+            .collect()
+    }
+}
+
+impl CompanionLists {
+    pub fn new(savefile: &Savefile) -> Self {
+        let companions = savefile
+            .companions
+            .iter()
+            .zip(
+                savefile
+                    .companion_symbols
+                    .iter()
+                    .map(Some)
+                    .chain(std::iter::repeat(None)),
+            )
+            .map(|(companion, symbol)| Companion {
+                name: companion.name.to_string(),
+                steam_id: companion.steam_id,
+                steam_id_v3: companion.steam_id_v3(),
+                steam_url: companion.steam_url(),
+                symbol: symbol.map(|s| s.symbol),
+            })
+            .collect::<Vec<_>>();
+
+        let met = (savefile.companions_met as usize).min(companions.len());
+        let (current, past) = companions.split_at(met);
+
+        Self {
+            current: current.to_vec(),
+            past: past.to_vec(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    #[test]
+    fn stable_json_schema() {
+        let savefile = test_savefile();
+        let info = serde_json::to_value(SaveInfo::new(&savefile, &Section::ALL)).unwrap();
+
+        assert_eq!(info["schema_version"], 1);
+        assert_eq!(info["platform"], "PC");
+        assert_eq!(info["stats"]["journey_count"], 21);
+        assert_eq!(info["stats"]["robe_color"], "Red");
+        assert_eq!(info["stats"]["robe_tier"], 4);
+        assert_eq!(info["symbol"]["id"], 7);
+        assert_eq!(
+            info["glyphs"][0]["found"],
+            serde_json::json!([true, false, true])
+        );
+        assert_eq!(info["murals"][2]["found"], serde_json::json!([true, true]));
+        assert_eq!(info["companions"]["current"][0]["name"], "Wanderer");
+        assert_eq!(info["companions"]["current"][0]["symbol"], 6);
+        assert_eq!(info["companions"]["past"][0]["name"], "Machine");
+    }
+
+    #[test]
+    fn selected_sections() {
+        let savefile = test_savefile();
+        let info = SaveInfo::new(&savefile, &[Section::Glyphs]);
+        let info = serde_json::to_value(info).unwrap();
+
+        assert!(info.get("glyphs").is_some());
+        assert!(info.get("stats").is_none());
+        assert!(info.get("companions").is_none());
+    }
+}
@@ -0,0 +1,561 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use clap::Parser as ArgParser;
+use directories::BaseDirs;
+use jrny_save::steam_id_v3;
+use tracing::debug;
+
+use crate::vdf::Value;
+use crate::{AppArgs, DIRS};
+
+
+pub(crate) const JOURNEY_APP_ID: &str = "638230";
+
+const SAVE_FILE_NAME: &str = "SAVE.BIN";
+
+/// Save directory inside a Windows user profile, relative to `AppData/Local`
+const LOCAL_SAVE_DIR: [&str; 2] = ["Annapurna Interactive", "Journey"];
+
+/// How deep to look for savefiles below a save directory
+const SEARCH_DEPTH: usize = 4;
+
+/// Difference between a 64-bit Steam ID and the 32-bit account ID
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Additional Steam installations to search
+    #[arg(long = "steam-dir")]
+    steam_dirs: Vec<PathBuf>,
+
+    /// Additional Wine prefixes to search
+    #[arg(long = "prefix")]
+    prefixes: Vec<PathBuf>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    SteamCloud,
+    Proton,
+    Wine,
+    Lutris,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub id: u32,
+    pub name: Option<String>,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub path: PathBuf,
+    pub source: Source,
+    pub account: Option<Account>,
+    pub modified: Option<DateTime<Local>>,
+}
+
+
+/// Searches the usual places Journey savefiles end up in, starting from a home directory
+#[derive(Debug, Clone)]
+pub(crate) struct Locator {
+    home: PathBuf,
+    steam_dirs: Vec<PathBuf>,
+    prefixes: Vec<PathBuf>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let mut locator = Locator::new()?;
+    locator
+        .steam_dirs
+        .extend(sub_args.steam_dirs.iter().cloned());
+    locator.prefixes.extend(sub_args.prefixes.iter().cloned());
+
+    let locations = locator.locate();
+
+    if locations.is_empty() {
+        println!("No savefiles found");
+        return Ok(());
+    }
+
+    for location in locations {
+        println!("{}", location.path.display());
+        println!("  Source:   {}", location.source);
+        if let Some(account) = &location.account {
+            println!("  Account:  {}", account);
+        }
+        if let Some(modified) = &location.modified {
+            println!("  Modified: {}", modified.format("%Y-%m-%d %H:%M:%S"));
+        }
+    }
+
+    Ok(())
+}
+
+
+/// `path` if one was given, otherwise the default savefile
+pub(crate) fn savefile_or_default(path: Option<&Path>) -> Result<PathBuf> {
+    match path {
+        Some(path) => Ok(path.to_owned()),
+        None => default_savefile(),
+    }
+}
+
+
+/// The savefile last opened in the TUI, or else the most recently modified one found
+pub(crate) fn default_savefile() -> Result<PathBuf> {
+    let state_path = DIRS.data_local_dir().join("active_savefile");
+
+    if let Ok(path) = fs::read_to_string(state_path) {
+        return Ok(PathBuf::from(path.trim_end()));
+    }
+
+    Locator::new()?
+        .locate()
+        .into_iter()
+        .next()
+        .map(|location| location.path)
+        .context("No savefile found, pass one explicitly")
+}
+
+
+impl Locator {
+    pub fn new() -> Result<Self> {
+        let dirs = BaseDirs::new().context("Failed to determine home directory")?;
+
+        Ok(Self::with_home(dirs.home_dir()))
+    }
+
+    pub fn with_home<P>(home: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            home: home.into(),
+            steam_dirs: Vec::new(),
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// Finds all savefiles, most recently modified first
+    pub fn locate(&self) -> Vec<Location> {
+        let steam_dirs = self.steam_dirs();
+        let names = account_names(&steam_dirs);
+
+        let mut locations = Vec::new();
+
+        for steam_dir in &steam_dirs {
+            for user_dir in read_dirs(&steam_dir.join("userdata")) {
+                let app_dir = user_dir.join(JOURNEY_APP_ID);
+                for path in find_savefiles(&app_dir, SEARCH_DEPTH) {
+                    locations.push(Location::new(path, Source::SteamCloud, &names));
+                }
+            }
+        }
+
+        for library in library_folders(&steam_dirs) {
+            let prefix = library
+                .join("steamapps/compatdata")
+                .join(JOURNEY_APP_ID)
+                .join("pfx");
+            for path in find_prefix_savefiles(&prefix) {
+                locations.push(Location::new(path, Source::Proton, &names));
+            }
+        }
+
+        for (prefix, source) in self.wine_prefixes() {
+            for path in find_prefix_savefiles(&prefix) {
+                locations.push(Location::new(path, source, &names));
+            }
+        }
+
+        // symlinked Steam directories turn up more than once
+        let mut seen = Vec::new();
+        locations.retain(|location| {
+            let path = fs::canonicalize(&location.path).unwrap_or_else(|_| location.path.clone());
+            let is_new = !seen.contains(&path);
+            seen.push(path);
+            is_new
+        });
+
+        locations.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+        locations
+    }
+
+    fn steam_dirs(&self) -> Vec<PathBuf> {
+        let defaults = [
+            ".steam/steam",
+            ".steam/root",
+            ".local/share/Steam",
+            ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+            "snap/steam/common/.local/share/Steam",
+        ];
+
+        let candidates = defaults
+            .iter()
+            .map(|dir| self.home.join(dir))
+            .chain(self.steam_dirs.iter().cloned());
+
+        dedup_dirs(candidates)
+    }
+
+    fn wine_prefixes(&self) -> Vec<(PathBuf, Source)> {
+        let mut prefixes = vec![(self.home.join(".wine"), Source::Wine)];
+
+        prefixes.extend(
+            self.prefixes
+                .iter()
+                .map(|prefix| (prefix.clone(), Source::Wine)),
+        );
+
+        // Lutris installs into ~/Games/<game> by default, configs may point elsewhere
+        for game_dir in read_dirs(&self.home.join("Games")) {
+            prefixes.push((game_dir.clone(), Source::Lutris));
+            prefixes.push((game_dir.join("prefix"), Source::Lutris));
+        }
+
+        for config_dir in [".config/lutris/games", ".local/share/lutris/games"] {
+            for config in read_files(&self.home.join(config_dir)) {
+                if let Some(prefix) = lutris_prefix(&config) {
+                    prefixes.push((prefix, Source::Lutris));
+                }
+            }
+        }
+
+        prefixes
+    }
+}
+
+
+impl Location {
+    fn new(path: PathBuf, source: Source, names: &HashMap<u32, String>) -> Self {
+        let account = account_id(&path).map(|id| Account {
+            id,
+            name: names.get(&id).cloned(),
+        });
+
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Local>::from);
+
+        Self {
+            path,
+            source,
+            account,
+            modified,
+        }
+    }
+}
+
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::SteamCloud => "Steam Cloud",
+            Self::Proton => "Proton",
+            Self::Wine => "Wine",
+            Self::Lutris => "Lutris",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} ({})", name, steam_id_v3(self.id)),
+            None => write!(f, "{}", steam_id_v3(self.id)),
+        }
+    }
+}
+
+
+/// Steam installations and all library folders listed in their `libraryfolders.vdf`
+fn library_folders(steam_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut libraries = steam_dirs.to_vec();
+
+    for steam_dir in steam_dirs {
+        for vdf_path in ["steamapps/libraryfolders.vdf", "config/libraryfolders.vdf"] {
+            let vdf_path = steam_dir.join(vdf_path);
+            if !vdf_path.exists() {
+                continue;
+            }
+
+            let folders = match Value::from_path(&vdf_path) {
+                Ok(value) => value,
+                Err(err) => {
+                    debug!("Skipping library folders: {:#}", err);
+                    continue;
+                }
+            };
+
+            let Some(folders) = folders.get("libraryfolders") else {
+                continue;
+            };
+
+            // older versions list the paths directly instead of in an object
+            let paths = folders.entries().iter().filter_map(|(_, folder)| {
+                folder
+                    .as_str()
+                    .or_else(|| folder.get("path").and_then(Value::as_str))
+            });
+
+            libraries.extend(paths.map(PathBuf::from));
+        }
+    }
+
+    dedup_dirs(libraries)
+}
+
+
+/// Maps account IDs to persona names of everyone who logged in to Steam on this machine
+fn account_names(steam_dirs: &[PathBuf]) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+
+    for steam_dir in steam_dirs {
+        let Ok(login_users) = Value::from_path(steam_dir.join("config/loginusers.vdf")) else {
+            continue;
+        };
+
+        let Some(users) = login_users.get("users") else {
+            continue;
+        };
+
+        for (steam_id, user) in users.entries() {
+            let id = steam_id.parse().ok().and_then(account_id_from_number);
+            let name = user
+                .get("PersonaName")
+                .or_else(|| user.get("AccountName"))
+                .and_then(Value::as_str);
+
+            if let (Some(id), Some(name)) = (id, name) {
+                names.insert(id, name.to_string());
+            }
+        }
+    }
+
+    names
+}
+
+
+/// Finds the account a savefile belongs to by the closest numeric directory in its path
+fn account_id(path: &Path) -> Option<u32> {
+    path.ancestors()
+        .skip(1)
+        .filter_map(|dir| dir.file_name()?.to_str()?.parse::<u64>().ok())
+        .filter(|&number| number.to_string() != JOURNEY_APP_ID)
+        .find_map(account_id_from_number)
+}
+
+
+/// Accepts both 64-bit Steam IDs and plain account IDs
+fn account_id_from_number(number: u64) -> Option<u32> {
+    let id = number.checked_sub(STEAM_ID64_BASE).unwrap_or(number);
+
+    u32::try_from(id).ok().filter(|&id| id != 0)
+}
+
+
+fn find_prefix_savefiles(prefix: &Path) -> Vec<PathBuf> {
+    let mut savefiles = Vec::new();
+
+    for user_dir in read_dirs(&prefix.join("drive_c/users")) {
+        let save_dir = LOCAL_SAVE_DIR
+            .iter()
+            .fold(user_dir.join("AppData/Local"), |dir, name| dir.join(name));
+        savefiles.extend(find_savefiles(&save_dir, SEARCH_DEPTH));
+    }
+
+    savefiles
+}
+
+
+fn find_savefiles(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut savefiles = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() && depth > 0 {
+            savefiles.extend(find_savefiles(&path, depth - 1));
+        } else if path.is_file() && entry.file_name().eq_ignore_ascii_case(SAVE_FILE_NAME) {
+            savefiles.push(path);
+        }
+    }
+
+    savefiles
+}
+
+
+/// Reads the `prefix:` entry of a Lutris game config
+fn lutris_prefix(config: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(config).ok()?;
+
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("prefix:")?.trim();
+        let value = value.trim_matches(|ch| ch == '"' || ch == '\'');
+        (!value.is_empty()).then(|| PathBuf::from(value))
+    })
+}
+
+
+fn read_dirs(dir: &Path) -> Vec<PathBuf> {
+    read_entries(dir, Path::is_dir)
+}
+
+
+fn read_files(dir: &Path) -> Vec<PathBuf> {
+    read_entries(dir, Path::is_file)
+}
+
+
+fn read_entries(dir: &Path, filter: fn(&Path) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| filter(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+}
+
+
+/// Keeps existing directories, dropping ones that resolve to an earlier one
+fn dedup_dirs<I>(dirs: I) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut seen = Vec::new();
+    let mut unique = Vec::new();
+
+    for dir in dirs {
+        let Ok(canonical) = fs::canonicalize(&dir) else {
+            continue;
+        };
+
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            unique.push(dir);
+        }
+    }
+
+    unique
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn touch(path: PathBuf) -> PathBuf {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"").unwrap();
+        path
+    }
+
+    #[test]
+    fn fake_tree() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let library = tempfile::tempdir().unwrap();
+        let library = library.path();
+
+        let steam = home.join(".local/share/Steam");
+        let cloud_save = touch(steam.join("userdata/12345/638230/remote/SAVE.BIN"));
+        touch(steam.join("userdata/12345/100/remote/SAVE.BIN"));
+
+        fs::create_dir_all(steam.join("config")).unwrap();
+        fs::write(
+            steam.join("config/loginusers.vdf"),
+            r#""users" { "76561197960278073" { "AccountName" "user" "PersonaName" "Wanderer" } }"#,
+        )
+        .unwrap();
+
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        fs::write(
+            steam.join("steamapps/libraryfolders.vdf"),
+            format!(
+                r#""libraryfolders" {{ "1" {{ "path" "{}" }} }}"#,
+                library.display()
+            ),
+        )
+        .unwrap();
+
+        let proton_save = touch(library.join(
+            "steamapps/compatdata/638230/pfx/drive_c/users/steamuser/AppData/Local/Annapurna \
+             Interactive/Journey/Steam/76561197960265729/SAVE.BIN",
+        ));
+
+        let lutris_save = touch(home.join(
+            "Games/journey/drive_c/users/user/AppData/Local/Annapurna Interactive/Journey/SAVE.BIN",
+        ));
+
+        let locations = Locator::with_home(home).locate();
+
+        let find = |path: &Path| {
+            locations
+                .iter()
+                .find(|location| location.path == path)
+                .unwrap_or_else(|| panic!("{} not found", path.display()))
+        };
+
+        assert_eq!(locations.len(), 3);
+
+        let cloud = find(&cloud_save);
+        assert_eq!(cloud.source, Source::SteamCloud);
+        assert_eq!(
+            cloud.account,
+            Some(Account {
+                id: 12345,
+                name: Some("Wanderer".to_string())
+            })
+        );
+        assert!(cloud.modified.is_some());
+
+        let proton = find(&proton_save);
+        assert_eq!(proton.source, Source::Proton);
+        assert_eq!(proton.account.as_ref().map(|account| account.id), Some(1));
+
+        let lutris = find(&lutris_save);
+        assert_eq!(lutris.source, Source::Lutris);
+        assert_eq!(lutris.account, None);
+    }
+
+    #[test]
+    fn lutris_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("journey.yml");
+        fs::write(
+            &config,
+            "game:\n  exe: journey.exe\n  prefix: /games/journey\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            lutris_prefix(&config),
+            Some(PathBuf::from("/games/journey"))
+        );
+    }
+}
@@ -1,5 +1,33 @@
+mod anonymize;
+mod backup;
+mod card;
+mod check;
+mod cloud;
+mod companions;
+mod convert;
 mod edit;
+mod export;
+mod history;
+mod hooks;
+mod info;
+mod locate;
+mod merge;
+mod metrics;
+mod overlay;
+mod recipe;
+mod record;
+mod report;
+mod roster;
+mod rpc;
+mod script;
+mod serve;
+mod show;
+mod slot;
+mod steam;
+mod test_util;
+mod timeline;
 mod tui;
+mod vdf;
 mod watcher;
 
 
@@ -38,6 +66,64 @@ pub(crate) struct AppArgs {
 pub(crate) enum CommandArgs {
     /// Edit verious aspect of save files
     Edit(edit::Args),
+
+    /// Convert save files between platform formats
+    Convert(convert::Args),
+
+    /// Replace companion names and ids for sharing save files
+    Anonymize(anonymize::Args),
+
+    /// Merge the progress of two save files
+    Merge(merge::Args),
+
+    /// Print the contents of a save file
+    Show(show::Args),
+
+    /// Search for save files in the usual Steam, Proton and Wine locations
+    Locate(locate::Args),
+
+    /// Browse, restore and prune stored versions of save files
+    Backup(backup::Args),
+
+    /// Keep named copies of save files to switch between progress states
+    Slot(slot::Args),
+
+    /// Record every version of a save file the game writes
+    #[cfg(feature = "watch")]
+    Record(record::Args),
+
+    /// Summarize recorded progress per play session
+    Timeline(timeline::Args),
+
+    /// List companions with the times they were seen, across all save files with --all
+    Companions(companions::Args),
+
+    /// Serve a save file as a JSON API with a live event stream
+    #[cfg(feature = "watch")]
+    Serve(serve::Args),
+
+    /// Render templates with save file data whenever the game writes it
+    #[cfg(feature = "watch")]
+    Overlay(overlay::Args),
+
+    /// Export save file progress as Prometheus metrics
+    #[cfg(feature = "watch")]
+    Metrics(metrics::Args),
+
+    /// Edit save files with a Rhai script
+    Script(script::Args),
+
+    /// Edit save files from other programs with JSON-RPC 2.0 over stdin and stdout
+    Rpc(rpc::Args),
+
+    /// Draw a shareable profile card as SVG
+    Card(card::Args),
+
+    /// Write a progress report as HTML or Markdown
+    Report(report::Args),
+
+    /// Export savefiles as CSV tables or into a SQLite database
+    Export(export::Args),
 }
 
 
@@ -49,6 +135,28 @@ fn main() -> Result<()> {
 
     match &args.command {
         Some(CommandArgs::Edit(sub_args)) => edit::execute(&args, sub_args)?,
+        Some(CommandArgs::Convert(sub_args)) => convert::execute(&args, sub_args)?,
+        Some(CommandArgs::Anonymize(sub_args)) => anonymize::execute(&args, sub_args)?,
+        Some(CommandArgs::Merge(sub_args)) => merge::execute(&args, sub_args)?,
+        Some(CommandArgs::Show(sub_args)) => show::execute(&args, sub_args)?,
+        Some(CommandArgs::Locate(sub_args)) => locate::execute(&args, sub_args)?,
+        Some(CommandArgs::Backup(sub_args)) => backup::execute(&args, sub_args)?,
+        Some(CommandArgs::Slot(sub_args)) => slot::execute(&args, sub_args)?,
+        #[cfg(feature = "watch")]
+        Some(CommandArgs::Record(sub_args)) => record::execute(&args, sub_args)?,
+        Some(CommandArgs::Timeline(sub_args)) => timeline::execute(&args, sub_args)?,
+        Some(CommandArgs::Companions(sub_args)) => companions::execute(&args, sub_args)?,
+        #[cfg(feature = "watch")]
+        Some(CommandArgs::Serve(sub_args)) => serve::execute(&args, sub_args)?,
+        #[cfg(feature = "watch")]
+        Some(CommandArgs::Overlay(sub_args)) => overlay::execute(&args, sub_args)?,
+        #[cfg(feature = "watch")]
+        Some(CommandArgs::Metrics(sub_args)) => metrics::execute(&args, sub_args)?,
+        Some(CommandArgs::Script(sub_args)) => script::execute(&args, sub_args)?,
+        Some(CommandArgs::Rpc(sub_args)) => rpc::execute(&args, sub_args)?,
+        Some(CommandArgs::Card(sub_args)) => card::execute(&args, sub_args)?,
+        Some(CommandArgs::Report(sub_args)) => report::execute(&args, sub_args)?,
+        Some(CommandArgs::Export(sub_args)) => export::execute(&args, sub_args)?,
         None => tui::execute(&args.tui_args)?,
     }
 
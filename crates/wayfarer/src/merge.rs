@@ -0,0 +1,58 @@
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::{CompanionPolicy, Savefile};
+
+use crate::edit::{confirm_overwrite, differs_from};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    left_path: PathBuf,
+    right_path: PathBuf,
+
+    #[arg(long, short)]
+    out_path: PathBuf,
+
+    /// Which savefile to take the companion lists from
+    #[arg(
+        long,
+        default_value = "newest",
+        value_parser = PossibleValuesParser::new(["left", "right", "newest"])
+    )]
+    companions: String,
+
+    /// Overwrites an existing output file without asking
+    #[arg(long, short)]
+    yes: bool,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let left = Savefile::from_reader(File::open(&sub_args.left_path)?)?;
+    let right = Savefile::from_reader(File::open(&sub_args.right_path)?)?;
+
+    let policy: CompanionPolicy = sub_args.companions.parse()?;
+
+    let (merged, report) = left.merge(&right, policy);
+
+    let mut data = Vec::new();
+    merged.write(Cursor::new(&mut data))?;
+
+    if !sub_args.yes && differs_from(&sub_args.out_path, &data) {
+        confirm_overwrite(&sub_args.out_path)?;
+    }
+
+    fs::write(&sub_args.out_path, data)?;
+
+    println!("left:  {}", sub_args.left_path.display());
+    println!("right: {}", sub_args.right_path.display());
+    print!("{}", report);
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+#![cfg(feature = "watch")]
+
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use clap::Parser as ArgParser;
+use jrny_save::{Savefile, LEVEL_NAMES};
+use tiny_http::{Header, Method, Response, Server};
+use tracing::{debug, warn};
+
+use crate::locate::savefile_or_default;
+use crate::watcher::FileWatcher;
+use crate::AppArgs;
+
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to export, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    /// Serves the metrics on /metrics instead of printing them once
+    #[arg(long, num_args = 0..=1, default_missing_value = "127.0.0.1:9184")]
+    listen: Option<SocketAddr>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let savefile = Savefile::from_path(&path)?;
+
+    let Some(addr) = sub_args.listen else {
+        print!("{}", render(&savefile, Utc::now()));
+        return Ok(());
+    };
+
+    let server =
+        Server::http(addr).map_err(|err| anyhow!("Failed to listen on {}: {}", addr, err))?;
+
+    let savefile = Arc::new(Mutex::new(savefile));
+
+    let watched = Arc::clone(&savefile);
+    let watched_path = path.clone();
+    let _watcher = FileWatcher::new(&path, move || {
+        if let Err(err) = reload(&watched, &watched_path) {
+            warn!("Failed to reload savefile: {:#}", err);
+        }
+    });
+
+    println!(
+        "Serving metrics of {} on http://{}/metrics",
+        path.display(),
+        addr
+    );
+
+    serve(&server, &savefile);
+
+    Ok(())
+}
+
+
+fn reload(savefile: &Mutex<Savefile>, path: &Path) -> Result<()> {
+    let reloaded = Savefile::from_path(path)?;
+    *savefile.lock().unwrap() = reloaded;
+
+    Ok(())
+}
+
+
+/// Answers scrapes until the server shuts down
+fn serve(server: &Server, savefile: &Mutex<Savefile>) {
+    for request in server.incoming_requests() {
+        debug!("{} {}", request.method(), request.url());
+
+        let response = match (request.method(), request.url()) {
+            (Method::Get, "/metrics") => {
+                let metrics = render(&savefile.lock().unwrap(), Utc::now());
+                let content_type = Header::from_bytes("Content-Type", CONTENT_TYPE).unwrap();
+                Response::from_string(metrics).with_header(content_type)
+            }
+            _ => Response::from_string("Metrics are served on /metrics\n").with_status_code(404),
+        };
+
+        if let Err(err) = request.respond(response) {
+            debug!("Failed to respond: {}", err);
+        }
+    }
+}
+
+
+/// Formats the savefile in the Prometheus text exposition format
+fn render(savefile: &Savefile, now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+
+    let mut gauge = |name: &str, help: &str, samples: &[(Vec<(&str, &str)>, f64)]| {
+        let _ = writeln!(out, "# HELP journey_{} {}", name, help);
+        let _ = writeln!(out, "# TYPE journey_{} gauge", name);
+
+        for (labels, value) in samples {
+            let labels = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+                .collect::<Vec<_>>();
+
+            if labels.is_empty() {
+                let _ = writeln!(out, "journey_{} {}", name, value);
+            } else {
+                let _ = writeln!(out, "journey_{}{{{}}} {}", name, labels.join(","), value);
+            }
+        }
+    };
+
+    let per_level = |levels: Vec<(usize, Vec<bool>)>, only_found: bool| {
+        levels
+            .into_iter()
+            .map(|(level, found)| {
+                let count = found.iter().filter(|&&found| found || !only_found).count();
+                (vec![("level", LEVEL_NAMES[level])], count as f64)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let color = savefile.robe.color().to_string();
+    let level = savefile.current_level.to_string();
+    let since_last_played = (now - savefile.last_played).num_milliseconds() as f64 / 1000.0;
+
+    gauge("journeys_completed", "Number of completed journeys.", &[(
+        vec![],
+        savefile.journey_count as f64,
+    )]);
+    gauge(
+        "glyphs_found",
+        "Glyphs found per level.",
+        &per_level(savefile.glyphs.all().collect(), true),
+    );
+    gauge(
+        "glyphs_total",
+        "Glyphs per level.",
+        &per_level(savefile.glyphs.all().collect(), false),
+    );
+    gauge(
+        "murals_found",
+        "Murals found per level.",
+        &per_level(savefile.murals.all().collect(), true),
+    );
+    gauge(
+        "murals_total",
+        "Murals per level.",
+        &per_level(savefile.murals.all().collect(), false),
+    );
+    gauge("robe_tier", "Tier of the robe.", &[(
+        vec![],
+        savefile.robe.tier().into(),
+    )]);
+    gauge(
+        "robe_color",
+        "Always 1, the color of the robe is the color label.",
+        &[(vec![("color", &color)], 1.0)],
+    );
+    gauge("scarf_length", "Length of the scarf.", &[(
+        vec![],
+        (*savefile.scarf_length.as_ref()).into(),
+    )]);
+    gauge(
+        "companions_met",
+        "Companions met on the current journey.",
+        &[(vec![], savefile.companions_met.into())],
+    );
+    gauge(
+        "total_companions_met",
+        "Companions met on all journeys.",
+        &[(vec![], savefile.total_companions_met.into())],
+    );
+    gauge(
+        "current_level",
+        "Always 1, the current level is the level label.",
+        &[(vec![("level", &level)], 1.0)],
+    );
+    gauge(
+        "seconds_since_last_played",
+        "Seconds since the game last wrote the savefile.",
+        &[(vec![], since_last_played)],
+    );
+
+    out
+}
+
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_util::{temp_savefile, test_savefile};
+
+    #[test]
+    fn exposition_format() {
+        let savefile = test_savefile();
+        let metrics = render(&savefile, savefile.last_played + Duration::seconds(90));
+
+        let lines = metrics.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..3], [
+            "# HELP journey_journeys_completed Number of completed journeys.",
+            "# TYPE journey_journeys_completed gauge",
+            "journey_journeys_completed 21",
+        ]);
+        assert!(lines.contains(&"journey_robe_tier 4"));
+        assert!(lines.contains(&"journey_robe_color{color=\"Red\"} 1"));
+        assert!(lines.contains(&"journey_current_level{level=\"Broken Bridge\"} 1"));
+        assert!(lines.contains(&"journey_seconds_since_last_played 90"));
+        assert!(lines.contains(&"journey_glyphs_total{level=\"Broken Bridge\"} 3"));
+
+        // every sample is preceded by its type
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let name = line.split(['{', ' ']).next().unwrap();
+            assert!(metrics.contains(&format!("# TYPE {} gauge\n", name)));
+        }
+
+        assert_eq!(escape_label("a\"b\\c"), r#"a\"b\\c"#);
+    }
+
+    #[test]
+    fn scrape() {
+        let (_dir, path) = temp_savefile();
+
+        let savefile = Arc::new(Mutex::new(Savefile::from_path(&path).unwrap()));
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        let serving = Arc::clone(&savefile);
+        thread::spawn(move || serve(&server, &serving));
+
+        let scrape = || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(
+                stream,
+                "GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = scrape();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.contains("\r\n\r\n# HELP journey_journeys_completed"));
+        assert!(response.contains("\njourney_journeys_completed 21\n"));
+
+        let mut changed = Savefile::from_path(&path).unwrap();
+        changed.journey_count = 22;
+        changed
+            .write(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        reload(&savefile, &path).unwrap();
+
+        assert!(scrape().contains("\njourney_journeys_completed 22\n"));
+    }
+}
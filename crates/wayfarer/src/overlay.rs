@@ -0,0 +1,256 @@
+#![cfg(feature = "watch")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use clap::Parser as ArgParser;
+use handlebars::Handlebars;
+use jrny_save::Savefile;
+use serde::Serialize;
+
+use crate::edit::write_atomic;
+use crate::info::{Companion, LevelStatus, SaveInfo, Section};
+use crate::locate::savefile_or_default;
+use crate::watcher::FileWatcher;
+use crate::AppArgs;
+
+
+const TEMPLATE_EXTENSIONS: [&str; 2] = ["hbs", "handlebars"];
+const HTML_EXTENSIONS: [&str; 4] = ["html", "htm", "svg", "xml"];
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Handlebars templates, e.g. `glyphs.txt.hbs` is rendered to `glyphs.txt`
+    #[arg(required = true)]
+    templates: Vec<PathBuf>,
+
+    /// Savefile to watch, defaults to the one last opened in the TUI
+    #[arg(short, long)]
+    savefile: Option<PathBuf>,
+
+    /// Writes the rendered files to this directory instead of next to the templates
+    #[arg(short, long)]
+    out_dir: Option<PathBuf>,
+
+    /// Renders the templates once instead of watching the savefile
+    #[arg(long)]
+    once: bool,
+}
+
+
+/// Templates and the files they're rendered to
+struct Overlay {
+    registry: Handlebars<'static>,
+    outputs: Vec<(PathBuf, PathBuf)>,
+}
+
+
+/// Data available to templates: every section of `wayfarer show` plus a few totals
+#[derive(Debug, Clone, Serialize)]
+struct TemplateData {
+    #[serde(flatten)]
+    info: SaveInfo,
+    glyphs_found: usize,
+    glyphs_total: usize,
+    murals_found: usize,
+    murals_total: usize,
+    latest_companion: Option<Companion>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.savefile.as_deref())?;
+
+    let mut overlay = Overlay::new(&sub_args.templates, sub_args.out_dir.as_deref())?;
+
+    render(&mut overlay, &path)?;
+
+    if sub_args.once {
+        return Ok(());
+    }
+
+    println!(
+        "Rendering {} templates whenever {} changes, press Ctrl+C to stop",
+        sub_args.templates.len(),
+        path.display()
+    );
+
+    let (changed_tx, changed_rx) = mpsc::channel();
+    let _watcher = FileWatcher::new(&path, move || {
+        let _ = changed_tx.send(());
+    });
+
+    while changed_rx.recv().is_ok() {
+        // a broken savefile or template is skipped until the next write
+        match render(&mut overlay, &path) {
+            Ok(()) => println!("{}  Rendered overlay", Local::now().format("%H:%M:%S")),
+            Err(err) => eprintln!("Warning: {:#}", err),
+        }
+    }
+
+    Ok(())
+}
+
+
+fn render(overlay: &mut Overlay, path: &Path) -> Result<()> {
+    overlay.render(&Savefile::from_path(path)?)
+}
+
+
+impl Overlay {
+    fn new(templates: &[PathBuf], out_dir: Option<&Path>) -> Result<Self> {
+        let outputs = templates
+            .iter()
+            .map(|template| Ok((template.clone(), output_path(template, out_dir)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            registry: Handlebars::new(),
+            outputs,
+        })
+    }
+
+    /// Renders every template, reading them again so they can be edited while watching
+    fn render(&mut self, savefile: &Savefile) -> Result<()> {
+        let data = TemplateData::new(savefile);
+
+        for (template, output) in &self.outputs {
+            let source = fs::read_to_string(template)
+                .with_context(|| format!("Failed to read template {}", template.display()))?;
+
+            let is_html = has_extension(output, &HTML_EXTENSIONS);
+            if is_html {
+                self.registry.register_escape_fn(handlebars::html_escape);
+            } else {
+                self.registry.register_escape_fn(handlebars::no_escape);
+            }
+
+            let rendered = self
+                .registry
+                .render_template(&source, &data)
+                .with_context(|| format!("Failed to render {}", template.display()))?;
+
+            // a source reading the output never sees half a file
+            write_atomic(output, rendered)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+impl TemplateData {
+    fn new(savefile: &Savefile) -> Self {
+        let info = SaveInfo::new(savefile, &Section::ALL);
+
+        let count = |levels: &Option<Vec<LevelStatus>>, only_found: bool| {
+            levels
+                .iter()
+                .flatten()
+                .flat_map(|level| &level.found)
+                .filter(|&&found| found || !only_found)
+                .count()
+        };
+
+        Self {
+            glyphs_found: count(&info.glyphs, true),
+            glyphs_total: count(&info.glyphs, false),
+            murals_found: count(&info.murals, true),
+            murals_total: count(&info.murals, false),
+            latest_companion: info
+                .companions
+                .as_ref()
+                .and_then(|companions| companions.current.last().cloned()),
+            info,
+        }
+    }
+}
+
+
+/// Strips the template extension, `overlay/glyphs.txt.hbs` becomes `overlay/glyphs.txt`
+fn output_path(template: &Path, out_dir: Option<&Path>) -> Result<PathBuf> {
+    if !has_extension(template, &TEMPLATE_EXTENSIONS) {
+        bail!(
+            "Template {} should end in .hbs or .handlebars",
+            template.display()
+        );
+    }
+
+    let Some(name) = template.file_stem() else {
+        bail!("Invalid template path {}", template.display())
+    };
+
+    let dir = match out_dir {
+        Some(dir) => dir,
+        None => template.parent().unwrap_or(Path::new("")),
+    };
+
+    Ok(dir.join(name))
+}
+
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    #[test]
+    fn renders_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut savefile = test_savefile();
+        savefile.path = PathBuf::from("Journey & Co/SAVE.BIN");
+
+        let text = dir.path().join("progress.txt.hbs");
+        fs::write(
+            &text,
+            "Glyphs: {{glyphs_found}}/{{glyphs_total}} in {{stats.current_level}}, tier {{stats.robe_tier}} of {{path}}",
+        )
+        .unwrap();
+
+        let html = dir.path().join("companion.html.hbs");
+        fs::write(&html, "<p>{{latest_companion.name}} in {{path}}</p>").unwrap();
+
+        let mut overlay = Overlay::new(&[text, html], None).unwrap();
+        overlay.render(&savefile).unwrap();
+
+        let data = TemplateData::new(&savefile);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("progress.txt")).unwrap(),
+            format!(
+                "Glyphs: {}/{} in Broken Bridge, tier 4 of Journey & Co/SAVE.BIN",
+                data.glyphs_found, data.glyphs_total
+            )
+        );
+
+        // only HTML outputs are escaped
+        let companion = data.latest_companion.unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("companion.html")).unwrap(),
+            format!("<p>{} in Journey &amp; Co/SAVE.BIN</p>", companion.name)
+        );
+    }
+
+    #[test]
+    fn output_paths() {
+        assert_eq!(
+            output_path(Path::new("overlay/glyphs.txt.hbs"), None).unwrap(),
+            PathBuf::from("overlay/glyphs.txt")
+        );
+        assert_eq!(
+            output_path(Path::new("glyphs.HANDLEBARS"), Some(Path::new("out"))).unwrap(),
+            PathBuf::from("out/glyphs")
+        );
+        assert!(output_path(Path::new("glyphs.txt"), None).is_err());
+    }
+}
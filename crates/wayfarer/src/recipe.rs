@@ -0,0 +1,256 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs};
+
+use anyhow::{Context, Result};
+use jrny_save::{Platform, RobeColor, Savefile};
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+use crate::edit::{level_id, Changes, Toggle};
+
+
+/// Batch edit read from a TOML file, e.g.
+///
+/// ```toml
+/// consistent = true
+///
+/// [when]
+/// robe_color = "red"
+///
+/// [set]
+/// robe_tier = 4
+/// glyphs = ["Pink Desert:2=on"]
+/// remove_companions = ["Machine"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Recipe {
+    /// Also change fields that depend on the edited ones
+    pub consistent: bool,
+
+    /// Savefiles that don't match are skipped
+    pub when: Conditions,
+
+    pub set: Changes,
+}
+
+
+/// Conditions a savefile has to meet, all given conditions have to match
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Conditions {
+    #[serde(deserialize_with = "deserialize_parsed")]
+    platform: Option<Platform>,
+
+    #[serde(deserialize_with = "deserialize_parsed")]
+    robe_color: Option<RobeColor>,
+
+    robe_tier: Option<u32>,
+
+    current_level: Option<String>,
+
+    symbol: Option<u32>,
+
+    min_journey_count: Option<u64>,
+
+    max_journey_count: Option<u64>,
+
+    /// Glyph states in the same notation as `--glyph`
+    glyphs: Vec<Toggle>,
+
+    /// Mural states in the same notation as `--mural`
+    murals: Vec<Toggle>,
+
+    /// Names of companions that have to be in the companion list
+    companions: Vec<String>,
+}
+
+
+impl Recipe {
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recipe {}", path.display()))?;
+        let recipe = toml::from_str::<Self>(&content)
+            .with_context(|| format!("Invalid recipe {}", path.display()))?;
+
+        // catch bad values before touching any savefile
+        recipe.set.edits()?;
+        if let Some(level) = &recipe.when.current_level {
+            level_id(level)?;
+        }
+
+        Ok(recipe)
+    }
+}
+
+
+impl Conditions {
+    /// Describes the first condition the savefile doesn't meet
+    pub fn mismatch(&self, savefile: &Savefile) -> Option<String> {
+        if self
+            .platform
+            .is_some_and(|platform| platform != savefile.platform)
+        {
+            return Some(format!("platform is {}", savefile.platform));
+        }
+
+        let robe_color = savefile.robe.color();
+        if self.robe_color.is_some_and(|color| color != robe_color) {
+            return Some(format!("robe color is {}", robe_color));
+        }
+
+        let robe_tier = savefile.robe.tier();
+        if self.robe_tier.is_some_and(|tier| tier != robe_tier) {
+            return Some(format!("robe tier is {}", robe_tier));
+        }
+
+        let current_level = savefile.current_level.to_string();
+        if self
+            .current_level
+            .as_ref()
+            .is_some_and(|level| *level != current_level)
+        {
+            return Some(format!("current level is {}", current_level));
+        }
+
+        let symbol = *savefile.symbol.as_ref();
+        if self.symbol.is_some_and(|id| id != symbol) {
+            return Some(format!("symbol is {}", symbol));
+        }
+
+        let journey_count = savefile.journey_count;
+        if self
+            .min_journey_count
+            .is_some_and(|min| journey_count < min)
+            || self
+                .max_journey_count
+                .is_some_and(|max| journey_count > max)
+        {
+            return Some(format!("journey count is {}", journey_count));
+        }
+
+        for glyph in &self.glyphs {
+            if savefile.glyphs.has_collected(glyph.level, glyph.index) != Some(glyph.enabled) {
+                return Some(format!("glyph {} does not match", toggle_name(glyph)));
+            }
+        }
+
+        for mural in &self.murals {
+            if savefile.murals.has_found(mural.level, mural.index) != Some(mural.enabled) {
+                return Some(format!("mural {} does not match", toggle_name(mural)));
+            }
+        }
+
+        for name in &self.companions {
+            if !savefile
+                .companions
+                .iter()
+                .any(|companion| companion.name == *name)
+            {
+                return Some(format!("companion {} is missing", name));
+            }
+        }
+
+        None
+    }
+}
+
+
+fn toggle_name(toggle: &Toggle) -> String {
+    format!(
+        "{}:{}",
+        jrny_save::LEVEL_NAMES[toggle.level],
+        toggle.index + 1
+    )
+}
+
+
+fn deserialize_parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| value.parse().map_err(de::Error::custom))
+        .transpose()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    fn recipe(content: &str) -> Recipe {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn conditions() {
+        let savefile = test_savefile();
+
+        let matching = recipe(
+            r#"
+            [when]
+            robe_color = "red"
+            min_journey_count = 21
+            glyphs = ["Chapter Select:1=on"]
+            companions = ["Wanderer"]
+            "#,
+        );
+        assert_eq!(matching.when.mismatch(&savefile), None);
+
+        let white_robe = recipe("when.robe_color = \"white\"");
+        assert_eq!(
+            white_robe.when.mismatch(&savefile).as_deref(),
+            Some("robe color is Red")
+        );
+
+        let missing = recipe("when.companions = [\"Nobody\"]");
+        assert!(missing.when.mismatch(&savefile).is_some());
+    }
+
+    #[test]
+    fn changes() {
+        let savefile = test_savefile();
+
+        let recipe = recipe(
+            r#"
+            consistent = true
+
+            [set]
+            robe_color = "white"
+            remove_companions = ["Machine"]
+            "#,
+        );
+        assert!(recipe.consistent);
+
+        let edited = recipe
+            .set
+            .apply(&savefile, jrny_save::EditMode::Consistent)
+            .unwrap();
+        assert_eq!(edited.collected_symbols, 21);
+        assert_eq!(edited.companions.count(), savefile.companions.count() - 1);
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert!(toml::from_str::<Recipe>("set.glyphs = [\"Nowhere:1=on\"]").is_err());
+        assert!(toml::from_str::<Recipe>("when.platform = \"xbox\"").is_err());
+        assert!(toml::from_str::<Recipe>("set.unknown = 1").is_err());
+        assert!(recipe("set.scarf_length = 31").set.edits().is_err());
+        assert!(recipe("set.companions_met = 17").set.edits().is_err());
+        assert!(recipe("set.journey_count = 1000001").set.edits().is_err());
+        assert!(recipe("set.total_collected_symbols = 4000000000")
+            .set
+            .edits()
+            .is_err());
+    }
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "watch")]
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use anyhow::Result;
+use chrono::Local;
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+
+use crate::history::{History, Record};
+use crate::hooks::Hooks;
+use crate::locate::savefile_or_default;
+use crate::watcher::FileWatcher;
+use crate::{roster, AppArgs};
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to record, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    /// Runs the hooks in this TOML file on progress, instead of hooks.toml in the config directory
+    #[arg(long)]
+    hooks: Option<PathBuf>,
+}
+
+
+/// Records a savefile and runs hooks on the progress since the previous version
+struct Recorder {
+    path: PathBuf,
+    history: History,
+    hooks: Hooks,
+    previous: Option<Savefile>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let mut recorder = Recorder {
+        path: path.clone(),
+        history: History::open()?,
+        hooks: Hooks::load(sub_args.hooks.as_deref())?,
+        previous: None,
+    };
+
+    println!("Recording {}, press Ctrl+C to stop", path.display());
+
+    let (changed_tx, changed_rx) = mpsc::channel();
+    let _watcher = FileWatcher::new(&path, move || {
+        let _ = changed_tx.send(());
+    });
+
+    recorder.record()?;
+
+    while changed_rx.recv().is_ok() {
+        // the game might still be writing, so a broken file is skipped until the next write
+        if let Err(err) = recorder.record() {
+            eprintln!("Warning: {:#}", err);
+        }
+    }
+
+    Ok(())
+}
+
+
+impl Recorder {
+    fn record(&mut self) -> Result<()> {
+        let Some(record) = self.history.record(&self.path)? else {
+            return Ok(());
+        };
+
+        print_record(&record);
+
+        let savefile = Savefile::from_path(&self.path)?;
+        roster::observe(&savefile)?;
+
+        if let Some(previous) = &self.previous {
+            self.hooks.fire(previous, &savefile);
+        }
+        self.previous = Some(savefile);
+
+        Ok(())
+    }
+}
+
+
+fn print_record(record: &Record) {
+    println!(
+        "{}  {:<16}  journeys {:>3}  glyphs {:>2}  murals {:>2}  companions {:>3}",
+        record
+            .recorded
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        record.current_level,
+        record.journey_count,
+        record.glyphs_found,
+        record.murals_found,
+        record.total_companions_met,
+    );
+}
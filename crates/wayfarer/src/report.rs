@@ -0,0 +1,438 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+
+use crate::check::{inconsistencies, problems};
+use crate::info::{Companion, LevelStatus, SaveInfo, Section};
+use crate::locate::savefile_or_default;
+use crate::AppArgs;
+
+
+const FOUND_SIGN: &str = "☑";
+const NOT_FOUND_SIGN: &str = "☐";
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }
+th { background: #f3f3f3; }
+.checklist { font-family: monospace; letter-spacing: 0.2em; }
+";
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to report on, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    #[arg(
+        long,
+        short,
+        default_value = "md",
+        value_parser = PossibleValuesParser::new(["md", "html"])
+    )]
+    format: String,
+
+    /// Earlier savefile to compare with field by field
+    #[arg(long, short)]
+    compare: Option<PathBuf>,
+
+    /// Writes the report to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+
+/// Parts of a report, rendered the same way in every format
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    List(Vec<String>),
+    Table {
+        header: Vec<String>,
+        rows: Vec<Vec<Cell>>,
+    },
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cell {
+    Text(String),
+    Link { text: String, url: String },
+    Checklist(Vec<bool>),
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let savefile = Savefile::from_path(path)?;
+
+    let earlier = match &sub_args.compare {
+        Some(path) => Some((path.as_path(), Savefile::from_path(path)?)),
+        None => None,
+    };
+    let earlier = earlier.as_ref().map(|(path, savefile)| (*path, savefile));
+
+    let blocks = report(&savefile, earlier);
+
+    let output = match sub_args.format.as_ref() {
+        "html" => render_html(&blocks),
+        _ => render_markdown(&blocks),
+    };
+
+    match &sub_args.output {
+        Some(output_path) => fs::write(output_path, output)
+            .with_context(|| format!("Failed to write {}", output_path.display()))?,
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+
+/// Collects everything the report shows about `savefile`, and what changed since `earlier`
+fn report(savefile: &Savefile, earlier: Option<(&Path, &Savefile)>) -> Vec<Block> {
+    let info = SaveInfo::new(savefile, &Section::ALL);
+    let text = |value: &dyn ToString| Cell::Text(value.to_string());
+
+    let mut blocks = vec![
+        Block::Heading("Journey Progress Report".to_string()),
+        Block::Paragraph(format!("File: {} ({})", info.path, info.platform)),
+    ];
+
+    if let Some(stats) = &info.stats {
+        let rows = [
+            ("Journeys Completed", text(&stats.journey_count)),
+            ("Total Companions Met", text(&stats.total_companions_met)),
+            (
+                "Total Symbols Collected",
+                text(&stats.total_collected_symbols),
+            ),
+            ("Symbols Collected", text(&stats.collected_symbols)),
+            ("Current Level", text(&stats.current_level)),
+            ("Companions Met", text(&stats.companions_met)),
+            ("Scarf Length", text(&stats.scarf_length)),
+            ("Symbol Number", text(&stats.symbol)),
+            ("Robe Color", text(&stats.robe_color)),
+            ("Robe Tier", text(&stats.robe_tier)),
+            ("Last Played", text(&stats.last_played)),
+        ];
+
+        blocks.push(Block::Heading("Stats".to_string()));
+        blocks.push(Block::Table {
+            header: vec!["Stat".to_string(), "Value".to_string()],
+            rows: rows
+                .into_iter()
+                .map(|(title, value)| vec![text(&title), value])
+                .collect(),
+        });
+    }
+
+    let levels = |title: &str, levels: &[LevelStatus]| {
+        let levels = levels
+            .iter()
+            .filter(|level| !level.found.is_empty())
+            .collect::<Vec<_>>();
+        let found = levels
+            .iter()
+            .map(|level| level.found.iter().filter(|&&found| found).count())
+            .sum::<usize>();
+        let total = levels.iter().map(|level| level.found.len()).sum::<usize>();
+
+        let rows = levels
+            .iter()
+            .map(|level| {
+                let found = level.found.iter().filter(|&&found| found).count();
+                vec![
+                    text(&level.level),
+                    Cell::Text(format!("{}/{}", found, level.found.len())),
+                    Cell::Checklist(level.found.clone()),
+                ]
+            })
+            .collect();
+
+        [
+            Block::Heading(format!("{} {}/{}", title, found, total)),
+            Block::Table {
+                header: vec!["Level".to_string(), "Found".to_string(), title.to_string()],
+                rows,
+            },
+        ]
+    };
+
+    if let Some(glyphs) = &info.glyphs {
+        blocks.extend(levels("Glyphs", glyphs));
+    }
+
+    if let Some(murals) = &info.murals {
+        blocks.extend(levels("Murals", murals));
+    }
+
+    if let Some(companions) = &info.companions {
+        let table = |list: &[Companion]| Block::Table {
+            header: vec![
+                "Name".to_string(),
+                "Steam ID".to_string(),
+                "Profile".to_string(),
+            ],
+            rows: list
+                .iter()
+                .map(|companion| {
+                    vec![
+                        text(&companion.name),
+                        text(&companion.steam_id_v3),
+                        Cell::Link {
+                            text: "Steam profile".to_string(),
+                            url: companion.steam_url.clone(),
+                        },
+                    ]
+                })
+                .collect(),
+        };
+
+        blocks.push(Block::Heading("Current Companions".to_string()));
+        blocks.push(table(&companions.current));
+        blocks.push(Block::Heading("Past Companions".to_string()));
+        blocks.push(table(&companions.past));
+    }
+
+    let mut issues = problems(savefile);
+    issues.extend(inconsistencies(savefile));
+
+    blocks.push(Block::Heading("Inconsistent Values".to_string()));
+    if issues.is_empty() {
+        blocks.push(Block::Paragraph("None found.".to_string()));
+    } else {
+        blocks.push(Block::List(issues));
+    }
+
+    if let Some((path, earlier)) = earlier {
+        let changes = earlier.diff(savefile);
+
+        blocks.push(Block::Heading(format!("Changes Since {}", path.display())));
+        if changes.is_empty() {
+            blocks.push(Block::Paragraph("No fields changed.".to_string()));
+        } else {
+            blocks.push(Block::Table {
+                header: vec![
+                    "Field".to_string(),
+                    "Earlier".to_string(),
+                    "Now".to_string(),
+                ],
+                rows: changes
+                    .iter()
+                    .map(|change| vec![text(&change.field), text(&change.old), text(&change.new)])
+                    .collect(),
+            });
+        }
+    }
+
+    blocks
+}
+
+
+fn checklist(found: &[bool]) -> String {
+    found
+        .iter()
+        .map(|&found| if found { FOUND_SIGN } else { NOT_FOUND_SIGN })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+
+    for (index, block) in blocks.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+
+        match block {
+            // the first heading is the title, all others are sections
+            Block::Heading(title) if index == 0 => {
+                let _ = writeln!(out, "# {}", escape_markdown(title));
+            }
+            Block::Heading(title) => {
+                let _ = writeln!(out, "## {}", escape_markdown(title));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "{}", escape_markdown(text));
+            }
+            Block::List(items) => {
+                for item in items {
+                    let _ = writeln!(out, "- {}", escape_markdown(item));
+                }
+            }
+            Block::Table { header, rows } => {
+                let header = header
+                    .iter()
+                    .map(|title| escape_markdown(title))
+                    .collect::<Vec<_>>();
+                let _ = writeln!(out, "| {} |", header.join(" | "));
+                let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+
+                for row in rows {
+                    let cells = row
+                        .iter()
+                        .map(|cell| match cell {
+                            Cell::Text(text) => escape_markdown(text),
+                            Cell::Link { text, url } => {
+                                format!("[{}](<{}>)", escape_markdown(text), url)
+                            }
+                            Cell::Checklist(found) => checklist(found),
+                        })
+                        .collect::<Vec<_>>();
+                    let _ = writeln!(out, "| {} |", cells.join(" | "));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+
+fn render_html(blocks: &[Block]) -> String {
+    let title = match blocks.first() {
+        Some(Block::Heading(title)) => title.as_str(),
+        _ => "Report",
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", escape_html(title));
+    let _ = writeln!(out, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+
+    for (index, block) in blocks.iter().enumerate() {
+        match block {
+            Block::Heading(title) => {
+                let tag = if index == 0 { "h1" } else { "h2" };
+                let _ = writeln!(out, "<{tag}>{}</{tag}>", escape_html(title), tag = tag);
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "<p>{}</p>", escape_html(text));
+            }
+            Block::List(items) => {
+                let _ = writeln!(out, "<ul>");
+                for item in items {
+                    let _ = writeln!(out, "<li>{}</li>", escape_html(item));
+                }
+                let _ = writeln!(out, "</ul>");
+            }
+            Block::Table { header, rows } => {
+                let _ = writeln!(out, "<table>\n<tr>");
+                for title in header {
+                    let _ = writeln!(out, "<th>{}</th>", escape_html(title));
+                }
+                let _ = writeln!(out, "</tr>");
+
+                for row in rows {
+                    let _ = writeln!(out, "<tr>");
+                    for cell in row {
+                        let _ = match cell {
+                            Cell::Text(text) => writeln!(out, "<td>{}</td>", escape_html(text)),
+                            Cell::Link { text, url } => writeln!(
+                                out,
+                                "<td><a href=\"{}\">{}</a></td>",
+                                escape_html(url),
+                                escape_html(text)
+                            ),
+                            Cell::Checklist(found) => {
+                                writeln!(out, "<td class=\"checklist\">{}</td>", checklist(found))
+                            }
+                        };
+                    }
+                    let _ = writeln!(out, "</tr>");
+                }
+                let _ = writeln!(out, "</table>");
+            }
+        }
+    }
+
+    let _ = writeln!(out, "</body>\n</html>");
+
+    out
+}
+
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+
+#[cfg(test)]
+mod test {
+    use jrny_save::{Edit, EditMode};
+
+    use super::*;
+    use crate::test_util::test_savefile;
+
+    #[test]
+    fn markdown_report() {
+        let savefile = test_savefile();
+        let earlier = savefile
+            .preview(&Edit::JourneyCount(20), EditMode::Plain)
+            .unwrap()
+            .0;
+
+        let report = render_markdown(&report(&savefile, Some((Path::new("old.bin"), &earlier))));
+
+        assert!(report.starts_with("# Journey Progress Report\n"));
+        assert!(report.contains("| Journeys Completed | 21 |"));
+        assert!(report.contains("## Glyphs 13/21"));
+        assert!(report.contains("| Pink Desert | 2/4 | ☑ ☐ ☐ ☑ |"));
+        assert!(report.contains("## Murals 5/10"));
+        assert!(report.contains("| Traveler | \\[U:1:"));
+        assert!(report.contains("(<https://steamcommunity.com/profiles/%5BU%3A1%3A"));
+        assert!(report.contains("## Changes Since old.bin"));
+        assert!(report.contains("| journey\\_count | 20 | 21 |"));
+    }
+
+    #[test]
+    fn html_report() {
+        let savefile = test_savefile();
+        let mut blocks = report(&savefile, None);
+        blocks.push(Block::Paragraph("<script>&</script>".to_string()));
+
+        let report = render_html(&blocks);
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<title>Journey Progress Report</title>"));
+        assert!(report.contains("<h2>Glyphs 13/21</h2>"));
+        assert!(report.contains("<a href=\"https://steamcommunity.com/profiles/"));
+        assert!(report.contains("<p>&lt;script&gt;&amp;&lt;/script&gt;</p>"));
+        assert!(!report.contains("Changes Since"));
+        // self-contained, nothing but the profile links points elsewhere
+        assert!(!report.contains("src="));
+        assert!(!report.contains("<link"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use jrny_save::Savefile;
+use serde::{Deserialize, Serialize};
+
+use crate::edit::write_atomic;
+use crate::info::CompanionLists;
+use crate::DIRS;
+
+
+const ROSTER_FILE_NAME: &str = "companions.json";
+
+
+/// Every companion seen in any savefile wayfarer loaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Roster {
+    companions: Vec<RosterEntry>,
+
+    /// Steam IDs of the companions in the last seen version of each savefile
+    savefiles: HashMap<PathBuf, Vec<u32>>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RosterEntry {
+    pub name: String,
+    pub steam_id: u32,
+    pub symbol: Option<u32>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// How often the companion newly showed up in a savefile
+    pub times_seen: u32,
+}
+
+
+impl Roster {
+    pub fn path() -> PathBuf {
+        DIRS.data_local_dir().join(ROSTER_FILE_NAME)
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = fs::read_to_string(path)?;
+
+        serde_json::from_str(&data).with_context(|| format!("Corrupt roster {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path())
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        write_atomic(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Adds the companions of `savefile` to the roster, returns whether anything changed
+    pub fn observe(&mut self, savefile: &Savefile, now: DateTime<Utc>) -> bool {
+        let lists = CompanionLists::new(savefile);
+        let companions = lists.current.iter().chain(&lists.past).collect::<Vec<_>>();
+
+        let ids = companions
+            .iter()
+            .map(|companion| companion.steam_id)
+            .collect::<Vec<_>>();
+
+        // the same savefile may be opened by different paths
+        let path = fs::canonicalize(&savefile.path).unwrap_or_else(|_| savefile.path.clone());
+
+        let previous = self.savefiles.get(&path).cloned();
+        if previous.as_ref() == Some(&ids) {
+            return false;
+        }
+
+        for companion in companions {
+            let was_present = previous
+                .as_ref()
+                .is_some_and(|ids| ids.contains(&companion.steam_id));
+
+            let entry = match self.entry_mut(companion.steam_id) {
+                Some(entry) => entry,
+                None => {
+                    self.companions.push(RosterEntry {
+                        name: companion.name.clone(),
+                        steam_id: companion.steam_id,
+                        symbol: companion.symbol,
+                        first_seen: now,
+                        last_seen: now,
+                        times_seen: 0,
+                    });
+                    self.companions.last_mut().unwrap()
+                }
+            };
+
+            entry.name = companion.name.clone();
+            entry.symbol = companion.symbol.or(entry.symbol);
+            entry.last_seen = now;
+            if !was_present {
+                entry.times_seen += 1;
+            }
+        }
+
+        self.savefiles.insert(path, ids);
+
+        true
+    }
+
+    /// Companions whose name or Steam ID contains `query`, most recently seen first
+    pub fn search(&self, query: &str) -> Vec<&RosterEntry> {
+        let query = query.trim().to_lowercase();
+
+        let mut found = self
+            .companions
+            .iter()
+            .filter(|entry| {
+                entry.name.to_lowercase().contains(&query)
+                    || entry.steam_id.to_string().contains(&query)
+            })
+            .collect::<Vec<_>>();
+
+        found.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+
+        found
+    }
+
+    fn entry_mut(&mut self, steam_id: u32) -> Option<&mut RosterEntry> {
+        self.companions
+            .iter_mut()
+            .find(|entry| entry.steam_id == steam_id)
+    }
+}
+
+
+impl RosterEntry {
+    pub fn steam_id_v3(&self) -> String {
+        jrny_save::steam_id_v3(self.steam_id)
+    }
+}
+
+
+/// Adds the companions of `savefile` to the stored roster
+pub(crate) fn observe(savefile: &Savefile) -> Result<()> {
+    let mut roster = Roster::load()?;
+
+    if roster.observe(savefile, Utc::now()) {
+        roster.save()?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use jrny_save::{Edit, EditMode};
+
+    use super::*;
+    use crate::test_util::{test_savefile, TEST_FILE};
+
+    #[test]
+    fn keeps_companions_across_versions() {
+        let mut savefile = test_savefile();
+        savefile.path = PathBuf::from("SAVE.BIN");
+        let count = savefile.companions.iter().count();
+
+        let mut roster = Roster::default();
+        let start = Utc::now();
+
+        assert!(roster.observe(&savefile, start));
+        assert!(!roster.observe(&savefile, start + Duration::hours(1)));
+        assert_eq!(roster.companions.len(), count);
+        assert!(roster.companions.iter().all(|entry| entry.times_seen == 1));
+
+        let first = savefile.companions.iter().next().unwrap().name.to_string();
+        let mut without_first = savefile.clone();
+        without_first
+            .apply(&Edit::RemoveCompanion(0), EditMode::Plain)
+            .unwrap();
+        assert!(roster.observe(&without_first, start + Duration::hours(2)));
+
+        // removed companions stay in the roster
+        let entry = |roster: &Roster| {
+            roster
+                .companions
+                .iter()
+                .find(|entry| entry.name == first)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(entry(&roster).last_seen, start);
+
+        // and count as seen again when they come back
+        roster.observe(&savefile, start + Duration::hours(3));
+        assert_eq!(entry(&roster).times_seen, 2);
+        assert_eq!(roster.companions.len(), count);
+    }
+
+    #[test]
+    fn same_savefile_by_other_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("remote")).unwrap();
+        fs::write(dir.path().join("SAVE.BIN"), TEST_FILE).unwrap();
+
+        let mut roster = Roster::default();
+        let savefile = Savefile::from_path(dir.path().join("SAVE.BIN")).unwrap();
+        assert!(roster.observe(&savefile, Utc::now()));
+
+        let savefile = Savefile::from_path(dir.path().join("remote/../SAVE.BIN")).unwrap();
+        assert!(!roster.observe(&savefile, Utc::now()));
+        assert_eq!(roster.savefiles.len(), 1);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("companions.json");
+
+        let mut savefile = test_savefile();
+        savefile.path = PathBuf::from("SAVE.BIN");
+
+        let mut roster = Roster::default();
+        roster.observe(&savefile, Utc::now());
+        roster.save_to(&path).unwrap();
+
+        let loaded = Roster::load_from(&path).unwrap();
+        assert_eq!(loaded.companions, roster.companions);
+        assert_eq!(loaded.search("").len(), roster.companions.len());
+    }
+}
@@ -0,0 +1,637 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser as ArgParser;
+use jrny_save::{Change, Edit, Savefile};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::backup::{BackupStore, Reason};
+use crate::check::problems;
+use crate::edit::{edit_mode, find_companion, level_id, write_savefile, Changes};
+use crate::info::{SaveInfo, Section};
+use crate::locate::savefile_or_default;
+#[cfg(feature = "watch")]
+use crate::watcher::FileWatcher;
+use crate::AppArgs;
+
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const FAILED: i64 = -32000;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Updates Steam Cloud's remotecache.vdf when saving
+    #[arg(long)]
+    steam_cloud: bool,
+}
+
+
+/// Lines from stdin and changes on disk, handled one after another
+enum Input {
+    Line(String),
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    FileChanged,
+    Closed,
+}
+
+
+/// Editing state of one frontend, the same the TUI keeps
+struct Session {
+    savefile: Option<Savefile>,
+    /// Version last read from or written to disk
+    saved: Option<Savefile>,
+    undo: Vec<Savefile>,
+    backups: BackupStore,
+    steam_cloud: bool,
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    input_tx: mpsc::Sender<Input>,
+    #[cfg(feature = "watch")]
+    watcher: Option<FileWatcher>,
+}
+
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    id: Option<Value>,
+}
+
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OpenParams {
+    /// Defaults to the savefile last opened in the TUI
+    path: Option<PathBuf>,
+}
+
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SetParams {
+    changes: Changes,
+    #[serde(default)]
+    consistent: bool,
+    /// Only returns the changes without applying them
+    #[serde(default)]
+    preview: bool,
+}
+
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Collectible {
+    Glyph,
+    Mural,
+}
+
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToggleParams {
+    kind: Collectible,
+    level: String,
+    /// Counting starts at 1
+    number: usize,
+}
+
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddCompanionParams {
+    name: String,
+    steam_id: u32,
+    symbol: u32,
+}
+
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RemoveCompanionParams {
+    /// Name or position of the companion
+    companion: String,
+}
+
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SaveParams {
+    /// Defaults to the opened path
+    path: Option<PathBuf>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let (input_tx, input_rx) = mpsc::channel();
+
+    let lines_tx = input_tx.clone();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if lines_tx.send(Input::Line(line)).is_err() {
+                break;
+            }
+        }
+        let _ = lines_tx.send(Input::Closed);
+    });
+
+    let mut session = Session::new(input_tx, BackupStore::open()?, sub_args.steam_cloud);
+    let mut stdout = io::stdout().lock();
+
+    while let Ok(input) = input_rx.recv() {
+        let output = match input {
+            Input::Line(line) if line.trim().is_empty() => continue,
+            Input::Line(line) => session.handle_line(&line),
+            Input::FileChanged => session.file_changed(),
+            Input::Closed => break,
+        };
+
+        if let Some(output) = output {
+            writeln!(stdout, "{}", output)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+
+impl Session {
+    fn new(input_tx: mpsc::Sender<Input>, backups: BackupStore, steam_cloud: bool) -> Self {
+        Self {
+            savefile: None,
+            saved: None,
+            undo: Vec::new(),
+            backups,
+            steam_cloud,
+            input_tx,
+            #[cfg(feature = "watch")]
+            watcher: None,
+        }
+    }
+
+    /// Handles a request or a batch of requests, returns the response if there is one
+    fn handle_line(&mut self, line: &str) -> Option<Value> {
+        let message = match serde_json::from_str::<Value>(line) {
+            Ok(message) => message,
+            Err(err) => return Some(error_response(Value::Null, PARSE_ERROR, err.to_string())),
+        };
+
+        match message {
+            Value::Array(batch) if batch.is_empty() => Some(error_response(
+                Value::Null,
+                INVALID_REQUEST,
+                "Empty batch".to_string(),
+            )),
+            Value::Array(batch) => {
+                let responses = batch
+                    .into_iter()
+                    .filter_map(|message| self.handle_message(message))
+                    .collect::<Vec<_>>();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_message(message),
+        }
+    }
+
+    fn handle_message(&mut self, message: Value) -> Option<Value> {
+        let request = match serde_json::from_value::<Request>(message) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            Ok(_) => {
+                return Some(error_response(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    "Only JSON-RPC 2.0 is supported".to_string(),
+                ))
+            }
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    err.to_string(),
+                ))
+            }
+        };
+
+        debug!("{} {}", request.method, request.params);
+
+        let result = self.call(&request.method, request.params);
+
+        // requests without id are notifications, which get no response
+        let id = request.id?;
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(err) => error_response(id, err.code, err.message),
+        })
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "open" => self.open(parse_params(params)?).map_err(RpcError::failed),
+            "get_fields" => self.get_fields().map_err(RpcError::failed),
+            "set_fields" => self
+                .set_fields(parse_params(params)?)
+                .map_err(RpcError::failed),
+            "toggle" => self.toggle(parse_params(params)?).map_err(RpcError::failed),
+            "add_companion" => {
+                let params: AddCompanionParams = parse_params(params)?;
+                self.edit(Edit::AddCompanion {
+                    name: params.name,
+                    steam_id: params.steam_id,
+                    symbol: params.symbol,
+                })
+                .map_err(RpcError::failed)
+            }
+            "remove_companion" => {
+                let params: RemoveCompanionParams = parse_params(params)?;
+                self.remove_companion(&params.companion)
+                    .map_err(RpcError::failed)
+            }
+            "validate" => self.validate().map_err(RpcError::failed),
+            "diff" => self.diff().map_err(RpcError::failed),
+            "save" => self.save(parse_params(params)?).map_err(RpcError::failed),
+            "undo" => self.undo().map_err(RpcError::failed),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method {}", method),
+            }),
+        }
+    }
+
+    fn open(&mut self, params: OpenParams) -> Result<Value> {
+        let path = savefile_or_default(params.path.as_deref())?;
+
+        let savefile = Savefile::from_path(path)?;
+
+        #[cfg(feature = "watch")]
+        {
+            let input_tx = self.input_tx.clone();
+            self.watcher = Some(FileWatcher::new(&savefile.path, move || {
+                let _ = input_tx.send(Input::FileChanged);
+            }));
+        }
+
+        self.saved = Some(savefile.clone());
+        self.savefile = Some(savefile);
+        self.undo.clear();
+
+        self.get_fields()
+    }
+
+    fn get_fields(&self) -> Result<Value> {
+        let savefile = self.savefile()?;
+
+        Ok(serde_json::to_value(SaveInfo::new(
+            savefile,
+            &Section::ALL,
+        ))?)
+    }
+
+    fn set_fields(&mut self, params: SetParams) -> Result<Value> {
+        let savefile = self.savefile()?;
+
+        let edited = params
+            .changes
+            .apply(savefile, edit_mode(params.consistent))?;
+        let changes = savefile.diff(&edited);
+
+        if !params.preview {
+            self.replace(edited);
+        }
+
+        Ok(json!({ "changes": changes_json(&changes), "applied": !params.preview }))
+    }
+
+    fn toggle(&mut self, params: ToggleParams) -> Result<Value> {
+        let savefile = self.savefile()?;
+
+        let level = level_id(&params.level)?;
+        let index = params
+            .number
+            .checked_sub(1)
+            .context("Invalid number 0, counting starts at 1")?;
+
+        let levels = match params.kind {
+            Collectible::Glyph => savefile.glyphs.all().collect::<Vec<_>>(),
+            Collectible::Mural => savefile.murals.all().collect::<Vec<_>>(),
+        };
+        let found = levels
+            .into_iter()
+            .find(|(id, _)| *id == level)
+            .and_then(|(_, found)| found.get(index).copied())
+            .with_context(|| {
+                format!(
+                    "{} has no {:?} {}",
+                    params.level, params.kind, params.number
+                )
+            })?;
+
+        let edit = match params.kind {
+            Collectible::Glyph => Edit::Glyph {
+                level,
+                index,
+                collected: !found,
+            },
+            Collectible::Mural => Edit::Mural {
+                level,
+                index,
+                found: !found,
+            },
+        };
+
+        self.edit(edit)
+    }
+
+    fn remove_companion(&mut self, companion: &str) -> Result<Value> {
+        let index = find_companion(self.savefile()?, companion)?;
+        self.edit(Edit::RemoveCompanion(index))
+    }
+
+    fn edit(&mut self, edit: Edit) -> Result<Value> {
+        let (edited, changes) = self.savefile()?.preview(&edit, Default::default())?;
+        self.replace(edited);
+
+        Ok(json!({ "changes": changes_json(&changes) }))
+    }
+
+    fn validate(&self) -> Result<Value> {
+        Ok(json!({ "problems": problems(self.savefile()?) }))
+    }
+
+    /// Unsaved changes
+    fn diff(&self) -> Result<Value> {
+        let saved = self.saved.as_ref().context("No savefile opened")?;
+
+        Ok(json!({ "changes": changes_json(&saved.diff(self.savefile()?)) }))
+    }
+
+    fn save(&mut self, params: SaveParams) -> Result<Value> {
+        let savefile = self.savefile()?;
+        let path = params.path.unwrap_or_else(|| savefile.path.clone());
+
+        let problems = problems(savefile);
+        if !problems.is_empty() {
+            bail!("Not saving an invalid savefile: {}", problems.join("; "));
+        }
+
+        let warnings = write_savefile(
+            &self.backups,
+            &path,
+            savefile,
+            self.steam_cloud,
+            Reason::Save,
+        )?
+        .warnings;
+
+        if path == savefile.path {
+            self.saved = Some(savefile.clone());
+        }
+
+        Ok(json!({ "path": path, "warnings": warnings }))
+    }
+
+    fn undo(&mut self) -> Result<Value> {
+        let previous = self.undo.pop().context("Nothing to undo")?;
+        let changes = self.savefile()?.diff(&previous);
+        self.savefile = Some(previous);
+
+        Ok(json!({ "changes": changes_json(&changes) }))
+    }
+
+    /// Reloads the savefile like the TUI does, the edits before can still be undone
+    fn file_changed(&mut self) -> Option<Value> {
+        let path = self.savefile.as_ref()?.path.clone();
+
+        let reloaded = match Savefile::from_path(&path) {
+            Ok(reloaded) => reloaded,
+            Err(err) => {
+                // the game might still be writing, the next write brings another notification
+                debug!("Failed to reload savefile: {}", err);
+                return None;
+            }
+        };
+
+        let previous = self.saved.replace(reloaded.clone())?;
+        let changes = previous.diff(&reloaded);
+        if changes.is_empty() {
+            return None;
+        }
+
+        let had_edits = !self.savefile.as_ref()?.diff(&previous).is_empty();
+        self.replace(reloaded);
+
+        Some(json!({
+            "jsonrpc": "2.0",
+            "method": "file_changed",
+            "params": {
+                "path": path,
+                "changes": changes_json(&changes),
+                "discarded_edits": had_edits,
+            },
+        }))
+    }
+
+    fn savefile(&self) -> Result<&Savefile> {
+        self.savefile.as_ref().context("No savefile opened")
+    }
+
+    fn replace(&mut self, savefile: Savefile) {
+        if let Some(previous) = self.savefile.replace(savefile) {
+            self.undo.push(previous);
+        }
+    }
+}
+
+
+impl RpcError {
+    fn failed(err: anyhow::Error) -> Self {
+        Self {
+            code: FAILED,
+            message: format!("{:#}", err),
+        }
+    }
+}
+
+
+/// Parses named parameters, missing parameters are taken as an empty object
+fn parse_params<T>(params: Value) -> Result<T, RpcError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let params = match params {
+        Value::Null => json!({}),
+        params => params,
+    };
+
+    serde_json::from_value(params).map_err(|err| RpcError {
+        code: INVALID_PARAMS,
+        message: err.to_string(),
+    })
+}
+
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
+
+
+fn changes_json(changes: &[Change]) -> Value {
+    changes
+        .iter()
+        .map(|change| json!({ "field": change.field, "old": change.old, "new": change.new }))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::temp_savefile;
+
+    fn call(session: &mut Session, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+        session.handle_line(&request.to_string()).unwrap()
+    }
+
+    fn open() -> (tempfile::TempDir, Session) {
+        let (dir, path) = temp_savefile();
+
+        let (input_tx, _) = mpsc::channel();
+        let backups = BackupStore::at(dir.path().join("backups")).unwrap();
+        let mut session = Session::new(input_tx, backups, false);
+        let opened = call(&mut session, "open", json!({ "path": path }));
+        assert_eq!(opened["result"]["stats"]["journey_count"], 21);
+
+        (dir, session)
+    }
+
+    #[test]
+    fn edit_and_undo() {
+        let (_dir, mut session) = open();
+
+        let set = call(
+            &mut session,
+            "set_fields",
+            json!({ "changes": { "journey_count": 30 } }),
+        );
+        assert_eq!(
+            set["result"]["changes"],
+            json!([{ "field": "journey_count", "old": "21", "new": "30" }])
+        );
+
+        let toggled = call(
+            &mut session,
+            "toggle",
+            json!({ "kind": "glyph", "level": "Broken Bridge", "number": 2 }),
+        );
+        assert_eq!(toggled["result"]["changes"][0]["new"], "on");
+
+        let removed = call(
+            &mut session,
+            "remove_companion",
+            json!({ "companion": "0" }),
+        );
+        assert!(!removed["result"]["changes"].as_array().unwrap().is_empty());
+
+        let diff = call(&mut session, "diff", Value::Null);
+        assert!(diff["result"]["changes"].as_array().unwrap().len() >= 3);
+
+        for _ in 0..3 {
+            call(&mut session, "undo", Value::Null);
+        }
+        let diff = call(&mut session, "diff", Value::Null);
+        assert_eq!(diff["result"]["changes"], json!([]));
+
+        let undo = call(&mut session, "undo", Value::Null);
+        assert_eq!(undo["error"]["code"], FAILED);
+
+        let validate = call(&mut session, "validate", Value::Null);
+        assert_eq!(validate["result"]["problems"], json!([]));
+    }
+
+    #[test]
+    fn reloads_changed_file() {
+        let (dir, mut session) = open();
+        call(
+            &mut session,
+            "set_fields",
+            json!({ "changes": { "scarf_length": 10 } }),
+        );
+
+        let path = dir.path().join("SAVE.BIN");
+        let mut savefile = Savefile::from_path(&path).unwrap();
+        savefile.journey_count = 22;
+        savefile.write(fs::File::create(&path).unwrap()).unwrap();
+
+        let notification = session.file_changed().unwrap();
+        assert_eq!(notification["method"], "file_changed");
+        assert_eq!(
+            notification["params"]["changes"][0]["field"],
+            "journey_count"
+        );
+        assert_eq!(notification["params"]["discarded_edits"], true);
+
+        // the edits from before the reload can be brought back
+        call(&mut session, "undo", Value::Null);
+        let fields = call(&mut session, "get_fields", Value::Null);
+        assert_eq!(fields["result"]["stats"]["scarf_length"], 10);
+
+        assert!(session.file_changed().is_none());
+    }
+
+    #[test]
+    fn protocol_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let (input_tx, _) = mpsc::channel();
+        let backups = BackupStore::at(dir.path().join("backups")).unwrap();
+        let mut session = Session::new(input_tx, backups, false);
+
+        let response = session.handle_line("{").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = call(&mut session, "fly", Value::Null);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = call(&mut session, "get_fields", Value::Null);
+        assert_eq!(response["error"]["code"], FAILED);
+
+        let response = call(&mut session, "toggle", json!({ "kind": "glyph" }));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // notifications get no response, batches one per request
+        let notification = json!({ "jsonrpc": "2.0", "method": "validate" });
+        assert!(session.handle_line(&notification.to_string()).is_none());
+
+        let batch = json!([
+            notification,
+            { "jsonrpc": "2.0", "method": "undo", "id": "a" },
+        ]);
+        let responses = session.handle_line(&batch.to_string()).unwrap();
+        assert_eq!(responses.as_array().unwrap().len(), 1);
+        assert_eq!(responses[0]["id"], "a");
+    }
+}
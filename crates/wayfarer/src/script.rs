@@ -0,0 +1,509 @@
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result};
+use clap::Parser as ArgParser;
+use jrny_save::{
+    Edit, EditMode, RobeColor, Savefile, LEVEL_NAMES, MAX_COLLECTED_SYMBOLS, MAX_COMPANIONS,
+    MAX_COMPANION_SYMBOL_ID, MAX_JOURNEY_COUNT, MAX_ROBE_TIER, MAX_SCARF_LENGTH, MAX_SYMBOL_ID,
+    MAX_TOTAL_COUNT, MIN_ROBE_TIER,
+};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+
+use crate::backup::{BackupStore, Reason};
+use crate::edit::{
+    check_range, edit_mode, expand_paths, find_companion, level_id, write_in_place, write_savefile,
+};
+use crate::info::CompanionLists;
+use crate::AppArgs;
+
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+
+/// Operations a script may run per savefile, stops scripts stuck in a loop
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Rhai script, run once for every savefile with the savefile in `save`
+    script: PathBuf,
+
+    /// Savefiles or glob patterns, the script can only write to these files
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Prints the fields a write would change without writing anything
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+
+    /// Also change fields that depend on the edited ones
+    #[arg(long)]
+    consistent: bool,
+
+    /// Updates Steam Cloud's remotecache.vdf next to written savefiles
+    #[arg(long)]
+    steam_cloud: bool,
+}
+
+
+/// Files a script may write to, only the savefiles named on the command line
+struct Sandbox {
+    allowed: Vec<PathBuf>,
+    /// Keeps the replaced savefiles, dry runs have none and only print the changes
+    store: Option<BackupStore>,
+    steam_cloud: bool,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let paths = expand_paths(&sub_args.paths)?;
+
+    let sandbox = Sandbox {
+        allowed: paths
+            .iter()
+            .filter_map(|path| fs::canonicalize(path).ok())
+            .collect(),
+        store: if sub_args.dry_run {
+            None
+        } else {
+            Some(BackupStore::open()?)
+        },
+        steam_cloud: sub_args.steam_cloud,
+    };
+
+    let engine = engine(sandbox, edit_mode(sub_args.consistent));
+
+    let ast = engine
+        .compile_file(sub_args.script.clone())
+        .map_err(|err| anyhow!("Invalid script {}: {}", sub_args.script.display(), err))?;
+
+    let mut failed = 0;
+
+    for path in &paths {
+        // an unreadable savefile fails like the script would, the others still run
+        let result = Savefile::from_path(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|savefile| run(&engine, &ast, savefile));
+        if let Err(err) = result {
+            failed += 1;
+            println!("{}: failed, {:#}", path.display(), err);
+        }
+    }
+
+    if failed > 0 {
+        bail!("Script failed for {} of {} savefiles", failed, paths.len());
+    }
+
+    Ok(())
+}
+
+
+/// Runs the script with `savefile` as `save`, returns the savefile as the script left it
+fn run(engine: &Engine, ast: &AST, savefile: Savefile) -> Result<Savefile> {
+    let levels = LEVEL_NAMES
+        .iter()
+        .map(|&name| Dynamic::from(name.to_string()))
+        .collect::<Array>();
+
+    let mut scope = Scope::new();
+    scope.push_constant("LEVELS", levels);
+    scope.push("save", savefile);
+
+    engine
+        .run_ast_with_scope(&mut scope, ast)
+        .map_err(|err| anyhow!("{}", err))?;
+
+    scope
+        .get_value::<Savefile>("save")
+        .ok_or_else(|| anyhow!("The script replaced `save`"))
+}
+
+
+impl Sandbox {
+    /// Writes `savefile` to `path` if the user named it
+    fn write(&self, savefile: &Savefile, path: &Path) -> Result<()> {
+        let allowed = fs::canonicalize(path).is_ok_and(|path| self.allowed.contains(&path));
+        if !allowed {
+            bail!(
+                "Writing to {} is not allowed, scripts can only write to the savefiles they were \
+                 given",
+                path.display()
+            );
+        }
+
+        let Some(store) = &self.store else {
+            let changes = Savefile::from_path(path)?.diff(savefile);
+            println!("{}: {} changes", path.display(), changes.len());
+            for change in changes {
+                println!("  {}", change);
+            }
+
+            return Ok(());
+        };
+
+        if path == savefile.path {
+            match write_in_place(store, path, savefile, self.steam_cloud)? {
+                Some(backup) => println!("{}: written, backup saved as {}", path.display(), backup),
+                None => println!("{}: written", path.display()),
+            }
+        } else {
+            for warning in
+                write_savefile(store, path, savefile, self.steam_cloud, Reason::Edit)?.warnings
+            {
+                eprintln!("Warning: {}", warning);
+            }
+            println!("{}: written", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Creates an engine with the savefile bindings and without access to other files
+fn engine(sandbox: Sandbox, mode: EditMode) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    engine.register_type_with_name::<Savefile>("Savefile");
+
+    let apply = move |savefile: &mut Savefile, edit: Edit| -> ScriptResult<()> {
+        savefile
+            .apply(&edit, mode)
+            .map(|_| ())
+            .map_err(|err| err.to_string().into())
+    };
+
+    // numbers are checked like the flags of `wayfarer edit`
+    let number = |field: &'static str, range: RangeInclusive<u32>| {
+        move |value: INT| -> ScriptResult<u32> {
+            let value =
+                u32::try_from(value).map_err(|_| format!("invalid {}: {}", field, value))?;
+            check_range(field, value, range.clone()).map_err(error)
+        }
+    };
+
+    engine
+        .register_get("platform", |s: &mut Savefile| s.platform.to_string())
+        .register_get("path", |s: &mut Savefile| s.path.display().to_string())
+        .register_get("last_played", |s: &mut Savefile| s.last_played.to_rfc3339())
+        .register_get_set(
+            "journey_count",
+            |s: &mut Savefile| s.journey_count as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = u64::try_from(value).map_err(|_| "invalid journey_count")?;
+                let value =
+                    check_range("journey_count", value, 0..=MAX_JOURNEY_COUNT).map_err(error)?;
+                apply(s, Edit::JourneyCount(value))
+            },
+        )
+        .register_get_set(
+            "total_companions_met",
+            |s: &mut Savefile| s.total_companions_met as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("total_companions_met", 0..=MAX_TOTAL_COUNT)(value)?;
+                apply(s, Edit::TotalCompanionsMet(value))
+            },
+        )
+        .register_get_set(
+            "total_collected_symbols",
+            |s: &mut Savefile| s.total_collected_symbols as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("total_collected_symbols", 0..=MAX_TOTAL_COUNT)(value)?;
+                apply(s, Edit::TotalCollectedSymbols(value))
+            },
+        )
+        .register_get_set(
+            "collected_symbols",
+            |s: &mut Savefile| s.collected_symbols as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("collected_symbols", 0..=MAX_COLLECTED_SYMBOLS)(value)?;
+                apply(s, Edit::CollectedSymbols(value))
+            },
+        )
+        .register_get_set(
+            "companions_met",
+            |s: &mut Savefile| s.companions_met as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("companions_met", 0..=MAX_COMPANIONS as u32)(value)?;
+                apply(s, Edit::CompanionsMet(value))
+            },
+        )
+        .register_get_set(
+            "scarf_length",
+            |s: &mut Savefile| *s.scarf_length.as_ref() as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("scarf_length", 0..=MAX_SCARF_LENGTH)(value)?;
+                apply(s, Edit::ScarfLength(value))
+            },
+        )
+        .register_get_set(
+            "symbol",
+            |s: &mut Savefile| *s.symbol.as_ref() as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("symbol", 0..=MAX_SYMBOL_ID)(value)?;
+                apply(s, Edit::Symbol(value))
+            },
+        )
+        .register_get_set(
+            "robe_tier",
+            |s: &mut Savefile| s.robe.tier() as INT,
+            move |s: &mut Savefile, value: INT| {
+                let value = number("robe_tier", MIN_ROBE_TIER..=MAX_ROBE_TIER)(value)?;
+                apply(s, Edit::RobeTier(value))
+            },
+        )
+        .register_get_set(
+            "robe_color",
+            |s: &mut Savefile| s.robe.color().to_string(),
+            move |s: &mut Savefile, value: String| {
+                let color = value.parse::<RobeColor>().map_err(|err| err.to_string())?;
+                apply(s, Edit::RobeColor(color))
+            },
+        )
+        .register_get_set(
+            "current_level",
+            |s: &mut Savefile| s.current_level.to_string(),
+            move |s: &mut Savefile, value: String| {
+                let id = level_id(&value).map_err(error)?;
+                apply(s, Edit::CurrentLevel(id as u64))
+            },
+        );
+
+    engine
+        .register_fn("glyphs", |s: &mut Savefile, level: &str| {
+            found_in_level(s.glyphs.all(), level)
+        })
+        .register_fn("murals", |s: &mut Savefile, level: &str| {
+            found_in_level(s.murals.all(), level)
+        })
+        .register_fn(
+            "set_glyph",
+            move |s: &mut Savefile, level: &str, number: INT, collected: bool| {
+                let (level, index) = position(level, number)?;
+                apply(s, Edit::Glyph {
+                    level,
+                    index,
+                    collected,
+                })
+            },
+        )
+        .register_fn(
+            "set_mural",
+            move |s: &mut Savefile, level: &str, number: INT, found: bool| {
+                let (level, index) = position(level, number)?;
+                apply(s, Edit::Mural {
+                    level,
+                    index,
+                    found,
+                })
+            },
+        );
+
+    engine
+        .register_get("companions", |s: &mut Savefile| {
+            let lists = CompanionLists::new(s);
+
+            let companion = |companion: &crate::info::Companion, current: bool| {
+                let mut map = Map::new();
+                map.insert("name".into(), companion.name.clone().into());
+                map.insert("steam_id".into(), (companion.steam_id as INT).into());
+                map.insert(
+                    "symbol".into(),
+                    companion
+                        .symbol
+                        .map_or(Dynamic::UNIT, |symbol| (symbol as INT).into()),
+                );
+                map.insert("current".into(), current.into());
+                Dynamic::from_map(map)
+            };
+
+            lists
+                .current
+                .iter()
+                .map(|c| companion(c, true))
+                .chain(lists.past.iter().map(|c| companion(c, false)))
+                .collect::<Array>()
+        })
+        .register_fn(
+            "add_companion",
+            move |s: &mut Savefile, name: &str, steam_id: INT, symbol: INT| {
+                let steam_id = u32::try_from(steam_id).map_err(|_| "invalid Steam ID")?;
+                let symbol = number("symbol", 0..=MAX_COMPANION_SYMBOL_ID)(symbol)?;
+                apply(s, Edit::AddCompanion {
+                    name: name.to_string(),
+                    steam_id,
+                    symbol,
+                })
+            },
+        )
+        .register_fn("remove_companion", move |s: &mut Savefile, name: &str| {
+            let index = find_companion(s, name).map_err(error)?;
+            apply(s, Edit::RemoveCompanion(index))
+        });
+
+    let sandbox = Rc::new(sandbox);
+    let write_sandbox = Rc::clone(&sandbox);
+
+    engine
+        .register_fn("write", move |s: &mut Savefile| -> ScriptResult<()> {
+            write_sandbox.write(s, &s.path).map_err(error)
+        })
+        .register_fn(
+            "write_to",
+            move |s: &mut Savefile, path: &str| -> ScriptResult<()> {
+                sandbox.write(s, Path::new(path)).map_err(error)
+            },
+        );
+
+    engine
+}
+
+
+/// Found states of the glyphs or murals of a level, empty for levels without any
+fn found_in_level<I>(mut levels: I, level: &str) -> ScriptResult<Array>
+where
+    I: Iterator<Item = (usize, Vec<bool>)>,
+{
+    let id = level_id(level).map_err(error)?;
+
+    let found = levels
+        .find(|(level, _)| *level == id)
+        .map(|(_, found)| found)
+        .unwrap_or_default();
+
+    Ok(found.into_iter().map(Dynamic::from).collect())
+}
+
+
+/// Level id and index of the 1-based `number` in the level
+fn position(level: &str, number: INT) -> ScriptResult<(usize, usize)> {
+    let level = level_id(level).map_err(error)?;
+
+    match usize::try_from(number) {
+        Ok(number) if number > 0 => Ok((level, number - 1)),
+        _ => Err(format!("invalid number {}, counting starts at 1", number).into()),
+    }
+}
+
+
+fn error(err: anyhow::Error) -> Box<EvalAltResult> {
+    format!("{:#}", err).into()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{test_savefile, TEST_FILE};
+
+    fn run_script(script: &str, allowed: Vec<PathBuf>) -> Result<Savefile> {
+        let sandbox = Sandbox {
+            allowed,
+            store: None,
+            steam_cloud: false,
+        };
+        let engine = engine(sandbox, EditMode::Plain);
+        let ast = engine.compile(script)?;
+
+        let mut savefile = test_savefile();
+        savefile.path = PathBuf::from("SAVE.BIN");
+
+        run(&engine, &ast, savefile)
+    }
+
+    #[test]
+    fn edits_savefile() {
+        let script = r#"
+            if save.robe_color == "Red" && save.robe_tier >= 3 {
+                for level in LEVELS {
+                    let glyphs = save.glyphs(level);
+                    if glyphs.filter(|found| found).len() < 2 {
+                        for number in 1..=glyphs.len() {
+                            save.set_glyph(level, number, true);
+                        }
+                    }
+                }
+            }
+            save.journey_count += 1;
+            save.remove_companion(save.companions[0].name);
+        "#;
+
+        let original = test_savefile();
+        let edited = run_script(script, Vec::new()).unwrap();
+
+        assert_eq!(edited.journey_count, 22);
+        assert_eq!(
+            edited.companions.iter().count(),
+            original.companions.iter().count() - 1
+        );
+        for ((_, before), (_, after)) in original.glyphs.all().zip(edited.glyphs.all()) {
+            let found = before.iter().filter(|&&found| found).count();
+            assert!(found >= 2 && after == before || after.iter().all(|&found| found));
+        }
+    }
+
+    #[test]
+    fn checks_values() {
+        assert!(run_script("save.robe_tier = 99;", Vec::new()).is_err());
+        assert!(run_script("save.current_level = \"Moon\";", Vec::new()).is_err());
+        assert!(run_script("save.set_glyph(\"Pink Desert\", 0, true);", Vec::new()).is_err());
+        assert!(run_script("import \"other\" as other;", Vec::new()).is_err());
+    }
+
+    #[test]
+    fn stops_endless_loops() {
+        let err = run_script("loop { save.journey_count; }", Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("Too many operations"));
+    }
+
+    #[test]
+    fn continues_after_invalid_savefile() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("count.rhai");
+        let broken = dir.path().join("BROKEN.BIN");
+        let valid = dir.path().join("SAVE.BIN");
+        fs::write(&script, "save.journey_count += 1;").unwrap();
+        fs::write(&broken, b"not a savefile").unwrap();
+        fs::write(&valid, TEST_FILE).unwrap();
+
+        let args = Args {
+            script,
+            paths: vec![broken, valid],
+            dry_run: true,
+            consistent: false,
+            steam_cloud: false,
+        };
+        let err = execute(&AppArgs::parse_from(["wayfarer"]), &args).unwrap_err();
+
+        assert_eq!(err.to_string(), "Script failed for 1 of 2 savefiles");
+    }
+
+    #[test]
+    fn writes_only_named_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let named = dir.path().join("SAVE.BIN");
+        let other = dir.path().join("OTHER.BIN");
+        fs::write(&named, TEST_FILE).unwrap();
+        fs::write(&other, TEST_FILE).unwrap();
+
+        let allowed = vec![fs::canonicalize(&named).unwrap()];
+        let write_to = |path: &Path| format!("save.write_to({:?});", path.display().to_string());
+
+        run_script(&write_to(&named), allowed.clone()).unwrap();
+
+        let err = run_script(&write_to(&other), allowed.clone()).unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+
+        // files that don't exist yet aren't named either
+        let new = dir.path().join("NEW.BIN");
+        assert!(run_script(&write_to(&new), allowed).is_err());
+        assert!(!new.exists());
+    }
+}
@@ -0,0 +1,579 @@
+#![cfg(feature = "watch")]
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use anyhow::{anyhow, Result};
+use clap::Parser as ArgParser;
+use jrny_save::{EditMode, Savefile};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{debug, info, warn};
+
+use crate::backup::{BackupStore, Reason};
+use crate::edit::{write_savefile, Changes};
+use crate::info::{SaveInfo, Section};
+use crate::locate::savefile_or_default;
+use crate::watcher::FileWatcher;
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to serve, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    bind: SocketAddr,
+
+    /// Enables POST /api/edit, which writes changes to the savefile
+    #[arg(long)]
+    allow_write: bool,
+
+    /// Updates Steam Cloud's remotecache.vdf when writing
+    #[arg(long)]
+    steam_cloud: bool,
+}
+
+
+/// Serves a savefile as JSON and pushes changes to event stream subscribers
+#[derive(Clone)]
+pub(crate) struct Api {
+    shared: Arc<Mutex<Shared>>,
+    backups: BackupStore,
+    allow_write: bool,
+    steam_cloud: bool,
+}
+
+
+struct Shared {
+    path: PathBuf,
+    savefile: Savefile,
+    subscribers: Vec<mpsc::Sender<String>>,
+}
+
+
+/// Error responses, rendered as `{"error": "..."}`
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let api = Api::new(
+        path.clone(),
+        BackupStore::open()?,
+        sub_args.allow_write,
+        sub_args.steam_cloud,
+    )?;
+
+    let server = Server::http(sub_args.bind)
+        .map_err(|err| anyhow!("Failed to listen on {}: {}", sub_args.bind, err))?;
+
+    let watched = api.clone();
+    let _watcher = FileWatcher::new(&path, move || {
+        if let Err(err) = watched.reload() {
+            warn!("Failed to reload savefile: {:#}", err);
+        }
+    });
+
+    println!("Serving {} on http://{}", path.display(), sub_args.bind);
+
+    api.serve(&server);
+
+    Ok(())
+}
+
+
+impl Api {
+    pub fn new(
+        path: PathBuf,
+        backups: BackupStore,
+        allow_write: bool,
+        steam_cloud: bool,
+    ) -> Result<Self> {
+        let savefile = Savefile::from_path(&path)?;
+
+        let shared = Shared {
+            path,
+            savefile,
+            subscribers: Vec::new(),
+        };
+
+        Ok(Self {
+            shared: Arc::new(Mutex::new(shared)),
+            backups,
+            allow_write,
+            steam_cloud,
+        })
+    }
+
+    /// Handles requests until the server shuts down
+    pub fn serve(&self, server: &Server) {
+        let hosts = own_hosts(server);
+
+        for mut request in server.incoming_requests() {
+            debug!("{} {}", request.method(), request.url());
+
+            if request.method() == &Method::Get && request.url() == "/api/events" {
+                let api = self.clone();
+                thread::spawn(move || api.stream_events(request));
+                continue;
+            }
+
+            let method = request.method().clone();
+            let url = request.url().to_owned();
+            let headers = request.headers().to_vec();
+
+            // other sites may read, but only the GET routes
+            let any_origin = method == Method::Get;
+
+            let response = match self.handle(&method, &url, &headers, &hosts, request.as_reader()) {
+                Ok(body) => json_response(200, &body, any_origin),
+                Err(err) => json_response(err.status, &json!({ "error": err.message }), any_origin),
+            };
+
+            if let Err(err) = request.respond(response) {
+                debug!("Failed to respond: {}", err);
+            }
+        }
+    }
+
+    /// Reads the savefile again and sends it to all event stream subscribers
+    pub fn reload(&self) -> Result<()> {
+        let mut shared = self.shared.lock().unwrap();
+
+        let savefile = Savefile::from_path(&shared.path)?;
+        let event = format_event(&savefile);
+
+        shared.savefile = savefile;
+        shared
+            .subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+
+        info!("Reloaded savefile");
+
+        Ok(())
+    }
+
+    fn handle(
+        &self,
+        method: &Method,
+        url: &str,
+        headers: &[Header],
+        hosts: &[String],
+        body: &mut dyn Read,
+    ) -> Result<serde_json::Value, ApiError> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+        match (method, path) {
+            (Method::Get, "/api/save") => {
+                let shared = self.shared.lock().unwrap();
+                to_json(SaveInfo::new(&shared.savefile, &Section::ALL))
+            }
+            (Method::Get, path) => {
+                let name = path.strip_prefix("/api/").unwrap_or_default();
+                let section = Section::from_name(name)
+                    .ok_or_else(|| ApiError::new(404, format!("No endpoint {}", path)))?;
+
+                let shared = self.shared.lock().unwrap();
+                let info = to_json(SaveInfo::new(&shared.savefile, &[section]))?;
+
+                Ok(info[name].clone())
+            }
+            (Method::Post, "/api/edit") if !self.allow_write => Err(ApiError::new(
+                403,
+                "Write endpoints are disabled, start the server with --allow-write",
+            )),
+            (Method::Post, "/api/edit") => {
+                check_write_request(headers, hosts)?;
+
+                let consistent = query.split('&').any(|param| param == "consistent=true");
+                self.edit(body, consistent)
+            }
+            _ => Err(ApiError::new(
+                405,
+                format!("{} is not allowed on {}", method, path),
+            )),
+        }
+    }
+
+    /// Applies the changes in the request body, which takes the fields of a recipe's `[set]`
+    fn edit(&self, body: &mut dyn Read, consistent: bool) -> Result<serde_json::Value, ApiError> {
+        let changes: Changes = serde_json::from_reader(body)
+            .map_err(|err| ApiError::new(400, format!("Invalid changes: {}", err)))?;
+
+        let mode = if consistent {
+            EditMode::Consistent
+        } else {
+            EditMode::Plain
+        };
+
+        let mut shared = self.shared.lock().unwrap();
+
+        let edited = changes
+            .apply(&shared.savefile, mode)
+            .map_err(|err| ApiError::new(400, format!("{:#}", err)))?;
+
+        let applied = shared.savefile.diff(&edited);
+
+        let path = shared.path.clone();
+        let warnings = write_savefile(
+            &self.backups,
+            &path,
+            &edited,
+            self.steam_cloud,
+            Reason::Edit,
+        )
+        .map_err(|err| ApiError::new(500, format!("{:#}", err)))?
+        .warnings;
+
+        shared.savefile = edited;
+
+        Ok(json!({
+            "changes": applied
+                .iter()
+                .map(|change| json!({ "field": change.field, "old": change.old, "new": change.new }))
+                .collect::<Vec<_>>(),
+            "warnings": warnings,
+        }))
+    }
+
+    /// Keeps the connection open and writes the savefile whenever it changes
+    fn stream_events(&self, request: Request) {
+        let (tx, rx) = mpsc::channel();
+
+        let first_event = {
+            let mut shared = self.shared.lock().unwrap();
+            shared.subscribers.push(tx);
+            format_event(&shared.savefile)
+        };
+
+        let mut writer = request.into_writer();
+
+        let header = "HTTP/1.1 200 OK\r\n\
+            Content-Type: text/event-stream\r\n\
+            Cache-Control: no-cache\r\n\
+            Access-Control-Allow-Origin: *\r\n\
+            Connection: close\r\n\r\n";
+
+        let events = std::iter::once(first_event).chain(rx);
+
+        let result = writer.write_all(header.as_bytes()).and_then(|_| {
+            for event in events {
+                writer.write_all(event.as_bytes())?;
+                writer.flush()?;
+            }
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            debug!("Event stream closed: {}", err);
+        }
+    }
+}
+
+
+impl ApiError {
+    fn new<S>(status: u16, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+
+fn to_json<T>(value: T) -> Result<serde_json::Value, ApiError>
+where
+    T: serde::Serialize,
+{
+    serde_json::to_value(value).map_err(|err| ApiError::new(500, err.to_string()))
+}
+
+
+/// Addresses the server may be reached at, as they appear in the `Host` header
+fn own_hosts(server: &Server) -> Vec<String> {
+    let Some(addr) = server.server_addr().to_ip() else {
+        return Vec::new();
+    };
+
+    vec![
+        addr.to_string(),
+        format!("localhost:{}", addr.port()),
+        format!("127.0.0.1:{}", addr.port()),
+    ]
+}
+
+
+/// Rejects writes a browser sends on behalf of another site.
+///
+/// Other sites can't send JSON without a preflight request, which is never allowed, and browsers
+/// name the site in `Origin`, which has to be the server itself. Both are only trusted for one of
+/// the server's own `hosts`, a site that rebinds its name to the server sends its own name.
+fn check_write_request(headers: &[Header], hosts: &[String]) -> Result<(), ApiError> {
+    let header = |name: &'static str| {
+        headers
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+
+    let is_json = header("Content-Type")
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("application/json"));
+    if !is_json {
+        return Err(ApiError::new(
+            415,
+            "Edits need Content-Type: application/json",
+        ));
+    }
+
+    let host = header("Host").unwrap_or_default();
+    if !hosts.iter().any(|own| own == host) {
+        return Err(ApiError::new(
+            403,
+            format!("Edits for host {} are not allowed", host),
+        ));
+    }
+
+    if let Some(origin) = header("Origin") {
+        if origin != format!("http://{}", host) {
+            return Err(ApiError::new(
+                403,
+                format!("Edits from {} are not allowed", origin),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+
+fn format_event(savefile: &Savefile) -> String {
+    let info = SaveInfo::new(savefile, &Section::ALL);
+    let data = serde_json::to_string(&info).unwrap_or_default();
+
+    format!("event: save\ndata: {}\n\n", data)
+}
+
+
+fn json_response(
+    status: u16,
+    body: &serde_json::Value,
+    any_origin: bool,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+
+    let response = Response::from_string(body.to_string() + "\n")
+        .with_status_code(status)
+        .with_header(content_type);
+
+    if any_origin {
+        let allow_origin = Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap();
+        response.with_header(allow_origin)
+    } else {
+        response
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpStream;
+    use std::{fs, str};
+
+    use super::*;
+    use crate::test_util::temp_savefile;
+
+    fn start(allow_write: bool) -> (tempfile::TempDir, Api, SocketAddr) {
+        let (dir, path) = temp_savefile();
+
+        let backups = BackupStore::at(dir.path().join("backups")).unwrap();
+        let api = Api::new(path, backups, allow_write, false).unwrap();
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        let serving = api.clone();
+        thread::spawn(move || serving.serve(&server));
+
+        (dir, api, addr)
+    }
+
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let headers = "Content-Type: application/json\r\n";
+        let (status, _, body) = request_with(addr, method, path, headers, body);
+
+        (status, body)
+    }
+
+    /// Sends a request with extra `headers`, returns the status, response head and body.
+    ///
+    /// `Host` is the server's address unless `headers` has one.
+    fn request_with(
+        addr: SocketAddr,
+        method: &str,
+        path: &str,
+        headers: &str,
+        body: &str,
+    ) -> (u16, String, serde_json::Value) {
+        let host = if headers.contains("Host: ") {
+            String::new()
+        } else {
+            format!("Host: {}\r\n", addr)
+        };
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\n{}{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            host,
+            headers,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+
+        (
+            status,
+            head.to_string(),
+            serde_json::from_str(body).unwrap(),
+        )
+    }
+
+    #[test]
+    fn sections() {
+        let (_dir, _api, addr) = start(false);
+
+        let (status, stats) = request(addr, "GET", "/api/stats", "");
+        assert_eq!(status, 200);
+        assert_eq!(stats["journey_count"], 21);
+
+        let (status, glyphs) = request(addr, "GET", "/api/glyphs", "");
+        assert_eq!(status, 200);
+        assert_eq!(glyphs[0]["level"], "Chapter Select");
+
+        let (status, save) = request(addr, "GET", "/api/save", "");
+        assert_eq!(status, 200);
+        assert_eq!(save["companions"]["current"].as_array().unwrap().len(), 6);
+
+        let (status, _) = request(addr, "GET", "/api/nothing", "");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn writes_need_flag() {
+        let (_dir, _api, addr) = start(false);
+        let (status, body) = request(addr, "POST", "/api/edit", r#"{"journey_count": 30}"#);
+        assert_eq!(status, 403);
+        assert!(body["error"].as_str().unwrap().contains("--allow-write"));
+
+        let (_dir, _api, addr) = start(true);
+        let (status, _) = request(addr, "POST", "/api/edit", r#"{"robe_tier": 99}"#);
+        assert_eq!(status, 400);
+        let (status, _) = request(addr, "POST", "/api/edit", r#"{"unknown": 1}"#);
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn cross_origin_writes() {
+        let (dir, _api, addr) = start(true);
+        let edit = r#"{"journey_count": 30}"#;
+
+        let (status, head, _) = request_with(addr, "GET", "/api/stats", "", "");
+        assert_eq!(status, 200);
+        assert!(head.contains("Access-Control-Allow-Origin: *"));
+
+        let form = "Content-Type: text/plain\r\n";
+        let (status, _, _) = request_with(addr, "POST", "/api/edit", form, edit);
+        assert_eq!(status, 415);
+
+        let foreign = "Content-Type: application/json\r\nOrigin: http://example.com\r\n";
+        let (status, head, body) = request_with(addr, "POST", "/api/edit", foreign, edit);
+        assert_eq!(status, 403);
+        assert!(body["error"].as_str().unwrap().contains("example.com"));
+        assert!(!head.contains("Access-Control-Allow-Origin"));
+
+        // a rebound name sends a matching pair that isn't the server's
+        let rebound = format!(
+            "Content-Type: application/json\r\nHost: example.com:{0}\r\nOrigin: http://example.com:{0}\r\n",
+            addr.port()
+        );
+        let (status, _, body) = request_with(addr, "POST", "/api/edit", &rebound, edit);
+        assert_eq!(status, 403);
+        assert!(body["error"].as_str().unwrap().contains("example.com"));
+
+        let same = format!(
+            "Content-Type: application/json\r\nOrigin: http://{}\r\n",
+            addr
+        );
+        let (status, head, body) = request_with(addr, "POST", "/api/edit", &same, edit);
+        assert_eq!(status, 200);
+        assert_eq!(body["changes"][0]["new"], "30");
+        assert!(!head.contains("Access-Control-Allow-Origin"));
+
+        let localhost = format!(
+            "Content-Type: application/json\r\nHost: localhost:{0}\r\nOrigin: http://localhost:{0}\r\n",
+            addr.port()
+        );
+        let (status, _, _) = request_with(addr, "POST", "/api/edit", &localhost, edit);
+        assert_eq!(status, 200);
+
+        // the replaced savefile is kept in the server's store
+        let backups = BackupStore::at(dir.path().join("backups")).unwrap();
+        assert!(!backups
+            .history(&dir.path().join("SAVE.BIN"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn event_stream() {
+        let (dir, api, addr) = start(false);
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /api/events HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut next_event = || loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(json) = line.strip_prefix("data: ") {
+                return serde_json::from_str::<serde_json::Value>(json).unwrap();
+            }
+        };
+
+        assert_eq!(next_event()["stats"]["journey_count"], 21);
+
+        let path = dir.path().join("SAVE.BIN");
+        let mut savefile = Savefile::from_path(&path).unwrap();
+        savefile.journey_count = 22;
+        savefile.write(fs::File::create(&path).unwrap()).unwrap();
+        api.reload().unwrap();
+
+        assert_eq!(next_event()["stats"]["journey_count"], 22);
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::builder::PossibleValuesParser;
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+use unicode_width::UnicodeWidthStr;
+
+use crate::info::{Companion, LevelStatus, SaveInfo, Section};
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    path: PathBuf,
+
+    #[arg(
+        long,
+        short,
+        default_value = "table",
+        value_parser = PossibleValuesParser::new(["table", "json", "yaml"])
+    )]
+    format: String,
+
+    /// Sections to show, shows all sections if omitted
+    #[arg(
+        long = "section",
+        short,
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(Section::NAMES)
+    )]
+    sections: Vec<String>,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let savefile = Savefile::from_path(&sub_args.path)?;
+
+    let sections = if sub_args.sections.is_empty() {
+        Section::ALL.to_vec()
+    } else {
+        sub_args
+            .sections
+            .iter()
+            .filter_map(|name| Section::from_name(name))
+            .collect()
+    };
+
+    let info = SaveInfo::new(&savefile, &sections);
+
+    let output = match sub_args.format.as_ref() {
+        "json" => serde_json::to_string_pretty(&info)? + "\n",
+        "yaml" => serde_yaml::to_string(&info)?,
+        _ => render_table(&info)?,
+    };
+
+    print!("{}", output);
+
+    Ok(())
+}
+
+
+pub(crate) fn render_table(info: &SaveInfo) -> Result<String> {
+    const FOUND_SIGN: &str = "◆";
+    const NOT_FOUND_SIGN: &str = "◇";
+
+    let mut out = String::new();
+
+    writeln!(out, "File: {} ({})", info.path, info.platform)?;
+
+    if let Some(stats) = &info.stats {
+        let rows = [
+            ("Journeys Completed", stats.journey_count.to_string()),
+            (
+                "Total Companions Met",
+                stats.total_companions_met.to_string(),
+            ),
+            (
+                "Total Symbols Collected",
+                stats.total_collected_symbols.to_string(),
+            ),
+            ("Symbols Collected", stats.collected_symbols.to_string()),
+            ("Current Level", stats.current_level.clone()),
+            ("Companions Met", stats.companions_met.to_string()),
+            ("Scarf Length", stats.scarf_length.to_string()),
+            ("Symbol Number", stats.symbol.to_string()),
+            ("Robe Color", stats.robe_color.clone()),
+            ("Robe Tier", stats.robe_tier.to_string()),
+            ("Last Played", stats.last_played.clone()),
+        ];
+
+        writeln!(out, "\nStats")?;
+        for (title, value) in rows {
+            writeln!(out, "  {:<25} {}", title, value)?;
+        }
+    }
+
+    if let Some(symbol) = &info.symbol {
+        writeln!(out, "\nSymbol")?;
+        for line in &symbol.lines {
+            writeln!(out, "  {}", line)?;
+        }
+    }
+
+    let mut write_levels = |title: &str, levels: &[LevelStatus]| -> Result<()> {
+        writeln!(out, "\n{}", title)?;
+        for level in levels {
+            let found = level
+                .found
+                .iter()
+                .map(|&found| if found { FOUND_SIGN } else { NOT_FOUND_SIGN })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, "  {:<20} {}", level.level, found)?;
+        }
+        Ok(())
+    };
+
+    if let Some(glyphs) = &info.glyphs {
+        write_levels("Glyphs", glyphs)?;
+    }
+
+    if let Some(murals) = &info.murals {
+        write_levels("Murals", murals)?;
+    }
+
+    if let Some(companions) = &info.companions {
+        let name_width = companions
+            .current
+            .iter()
+            .chain(companions.past.iter())
+            .map(|companion| companion.name.width())
+            .max()
+            .unwrap_or_default();
+
+        let mut write_companions = |title: &str, list: &[Companion]| -> Result<()> {
+            writeln!(out, "\n{} Companions", title)?;
+            for companion in list {
+                // pad by display width, format padding would count chars instead
+                let padding = " ".repeat(name_width - companion.name.width());
+                writeln!(
+                    out,
+                    "  {}{}  {}",
+                    companion.name, padding, companion.steam_url
+                )?;
+            }
+            Ok(())
+        };
+
+        write_companions("Current", &companions.current)?;
+        write_companions("Past", &companions.past)?;
+    }
+
+    Ok(out)
+}
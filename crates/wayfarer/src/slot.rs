@@ -0,0 +1,362 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use clap::Parser as ArgParser;
+use jrny_save::Savefile;
+
+use crate::backup::{BackupStore, Reason};
+use crate::edit::write_data;
+use crate::locate::savefile_or_default;
+use crate::{AppArgs, DIRS};
+
+
+const SLOT_EXTENSION: &str = "bin";
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    #[command(subcommand)]
+    command: SlotCommand,
+}
+
+
+#[derive(Debug, Clone, clap::Subcommand)]
+enum SlotCommand {
+    /// Copy a savefile into a named slot
+    Save(SaveArgs),
+
+    /// Copy a slot over a savefile, the replaced savefile is kept in the backups
+    Load(LoadArgs),
+
+    /// List all slots
+    List,
+
+    /// Remove a slot
+    Delete(DeleteArgs),
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct SaveArgs {
+    name: String,
+
+    /// Savefile to store, defaults to the one last opened in the TUI
+    #[arg(long, short)]
+    path: Option<PathBuf>,
+
+    /// Replaces an existing slot of the same name
+    #[arg(long, short)]
+    force: bool,
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct LoadArgs {
+    name: String,
+
+    /// Savefile to replace, defaults to the one last opened in the TUI
+    #[arg(long, short)]
+    path: Option<PathBuf>,
+
+    /// Updates Steam Cloud's remotecache.vdf next to the replaced savefile
+    #[arg(long)]
+    steam_cloud: bool,
+}
+
+
+#[derive(Debug, Clone, ArgParser)]
+struct DeleteArgs {
+    name: String,
+}
+
+
+/// Named copies of savefiles kept by wayfarer
+#[derive(Debug, Clone)]
+pub(crate) struct SlotStore {
+    dir: PathBuf,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Slot {
+    pub name: String,
+    pub modified: Option<DateTime<Local>>,
+    pub summary: Option<SlotSummary>,
+}
+
+
+/// What sets the progress states of slots apart
+#[derive(Debug, Clone)]
+pub struct SlotSummary {
+    pub robe: String,
+    pub level: String,
+    pub journey_count: u64,
+    /// Percentage of all glyphs and murals found
+    pub completion: u32,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let store = SlotStore::open()?;
+
+    match &sub_args.command {
+        SlotCommand::Save(args) => {
+            let path = savefile_or_default(args.path.as_deref())?;
+            store.save(&args.name, &path, args.force)?;
+            println!("Saved {} to slot {}", path.display(), args.name);
+        }
+        SlotCommand::Load(args) => {
+            let path = savefile_or_default(args.path.as_deref())?;
+            let backups = BackupStore::open()?;
+            let (backup, warnings) = store.load(&args.name, &backups, &path, args.steam_cloud)?;
+            for warning in warnings {
+                eprintln!("Warning: {}", warning);
+            }
+            println!("Loaded slot {} into {}", args.name, path.display());
+            if let Some(backup) = backup {
+                println!("Previous savefile kept as backup {}", backup);
+            }
+        }
+        SlotCommand::List => list(&store)?,
+        SlotCommand::Delete(args) => {
+            store.delete(&args.name)?;
+            println!("Deleted slot {}", args.name);
+        }
+    }
+
+    Ok(())
+}
+
+
+fn list(store: &SlotStore) -> Result<()> {
+    let slots = store.list()?;
+
+    if slots.is_empty() {
+        println!("No slots saved");
+        return Ok(());
+    }
+
+    let name_width = slots.iter().map(|slot| slot.name.len()).max().unwrap_or(0);
+
+    for slot in slots {
+        let modified = slot
+            .modified
+            .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        match slot.summary {
+            Some(summary) => println!(
+                "{:<name_width$}  {}  {:<10}  {:<16}  {:>3}%  {} journeys",
+                slot.name,
+                modified,
+                summary.robe,
+                summary.level,
+                summary.completion,
+                summary.journey_count,
+            ),
+            None => println!("{:<name_width$}  {}  unreadable", slot.name, modified),
+        }
+    }
+
+    Ok(())
+}
+
+
+impl SlotStore {
+    pub fn open() -> Result<Self> {
+        Self::at(DIRS.data_local_dir().join("slots"))
+    }
+
+    pub fn at<P>(dir: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create slot directory {}", dir.display()))?;
+
+        Ok(Self { dir })
+    }
+
+    /// All slots sorted by name
+    pub fn list(&self) -> Result<Vec<Slot>> {
+        let mut slots = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+
+            if path.extension().map_or(true, |ext| ext != SLOT_EXTENSION) {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::from);
+
+            let summary = Savefile::from_path(&path)
+                .ok()
+                .map(|savefile| SlotSummary::new(&savefile));
+
+            slots.push(Slot {
+                name: name.to_string(),
+                modified,
+                summary,
+            });
+        }
+
+        slots.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(slots)
+    }
+
+    /// Copies the savefile at `path` into the slot `name`
+    pub fn save(&self, name: &str, path: &Path, force: bool) -> Result<()> {
+        let slot_path = self.slot_path(name)?;
+
+        if !force && slot_path.exists() {
+            bail!("Slot {} already exists, use --force to replace it", name);
+        }
+
+        let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Savefile::from_reader(Cursor::new(&data))
+            .with_context(|| format!("{} is not a savefile", path.display()))?;
+
+        fs::write(&slot_path, data)?;
+
+        Ok(())
+    }
+
+    /// Copies the slot `name` over the savefile at `path`, backing it up to `backups`.
+    ///
+    /// Returns the backup id of the replaced savefile, if there was one, and warnings about the
+    /// Steam Cloud metadata.
+    pub fn load(
+        &self,
+        name: &str,
+        backups: &BackupStore,
+        path: &Path,
+        steam_cloud: bool,
+    ) -> Result<(Option<String>, Vec<String>)> {
+        let data = self.read(name)?;
+
+        let written = write_data(backups, path, &data, steam_cloud, Reason::Slot)?;
+
+        Ok((written.backup, written.warnings))
+    }
+
+    pub fn read(&self, name: &str) -> Result<Vec<u8>> {
+        let slot_path = self.slot_path(name)?;
+
+        fs::read(slot_path).with_context(|| format!("No slot named {}", name))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let slot_path = self.slot_path(name)?;
+
+        fs::remove_file(slot_path).with_context(|| format!("No slot named {}", name))
+    }
+
+    fn slot_path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|ch| ch.is_alphanumeric() || "-_. ".contains(ch));
+
+        if !valid {
+            bail!(
+                "Invalid slot name {:?}, use letters, digits, spaces, '-', '_' and '.'",
+                name
+            );
+        }
+
+        Ok(self.dir.join(format!("{}.{}", name, SLOT_EXTENSION)))
+    }
+}
+
+
+impl SlotSummary {
+    fn new(savefile: &Savefile) -> Self {
+        let (found, total) = savefile
+            .glyphs
+            .all()
+            .chain(savefile.murals.all())
+            .flat_map(|(_, found)| found)
+            .fold((0, 0), |(found, total), is_found| {
+                (found + u32::from(is_found), total + 1)
+            });
+
+        Self {
+            robe: format!("{} {}", savefile.robe.color(), savefile.robe.tier()),
+            level: savefile.current_level.to_string(),
+            journey_count: savefile.journey_count,
+            completion: (found * 100).checked_div(total).unwrap_or(0),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{temp_savefile, TEST_FILE};
+
+    #[test]
+    fn save_list_delete() {
+        let (dir, path) = temp_savefile();
+        let store = SlotStore::at(dir.path().join("slots")).unwrap();
+
+        store.save("mid-game", &path, false).unwrap();
+        assert!(store.save("mid-game", &path, false).is_err());
+        store.save("mid-game", &path, true).unwrap();
+        store.save("fresh", &path, false).unwrap();
+
+        let slots = store.list().unwrap();
+        let names = slots
+            .iter()
+            .map(|slot| slot.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["fresh", "mid-game"]);
+
+        let summary = slots[0].summary.as_ref().unwrap();
+        assert_eq!(summary.level, "Broken Bridge");
+        assert_eq!(summary.journey_count, 21);
+        assert!(summary.completion <= 100);
+
+        assert_eq!(store.read("fresh").unwrap(), TEST_FILE);
+
+        store.delete("fresh").unwrap();
+        assert!(store.delete("fresh").is_err());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SlotStore::at(dir.path()).unwrap();
+
+        assert!(store.slot_path("white robe").is_ok());
+        assert!(store.slot_path("").is_err());
+        assert!(store.slot_path("../escape").is_err());
+        assert!(store.slot_path("a/b").is_err());
+    }
+
+    #[test]
+    fn rejects_non_savefiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SlotStore::at(dir.path().join("slots")).unwrap();
+
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "not a savefile").unwrap();
+
+        assert!(store.save("notes", &path, false).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::edit::write_atomic;
+use crate::DIRS;
+
+
+const STEAM_FILE_NAME: &str = "steam.toml";
+const CACHE_FILE_NAME: &str = "steam_profiles.json";
+const DEFAULT_BASE_URL: &str = "https://api.steampowered.com";
+
+/// Added to the account id of a companion to get the 64 bit Steam ID the API expects
+const STEAM_ID64_BASE: u64 = 76_561_197_960_265_728;
+
+/// Most Steam IDs the API accepts per request
+const MAX_IDS_PER_REQUEST: usize = 100;
+
+
+/// Settings for looking up companion profiles with the Steam Web API, read from a TOML file, e.g.
+///
+/// ```toml
+/// api_key = "0123456789ABCDEF0123456789ABCDEF"
+/// base_url = "http://127.0.0.1:8080"   # defaults to https://api.steampowered.com
+/// cache_hours = 24
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SteamApi {
+    api_key: String,
+    base_url: String,
+
+    /// Cached profiles are looked up again once they are older than this
+    cache_hours: u32,
+
+    timeout_secs: u64,
+}
+
+
+/// What the Steam Web API reports about a companion's account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub persona_name: String,
+    pub avatar_url: String,
+    pub profile_url: String,
+    pub visibility: Visibility,
+    pub fetched_at: DateTime<Utc>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Private or friends only, the API doesn't tell them apart
+    Private,
+    Public,
+}
+
+
+#[derive(Debug, Deserialize)]
+struct SummariesResponse {
+    response: Summaries,
+}
+
+
+#[derive(Debug, Deserialize)]
+struct Summaries {
+    players: Vec<Summary>,
+}
+
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    steamid: String,
+    personaname: String,
+    #[serde(default)]
+    avatarfull: String,
+    #[serde(default)]
+    profileurl: String,
+    #[serde(default)]
+    communityvisibilitystate: u8,
+}
+
+
+impl Default for SteamApi {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            cache_hours: 24,
+            timeout_secs: 5,
+        }
+    }
+}
+
+
+impl SteamApi {
+    /// Default location of the settings file
+    pub fn path() -> PathBuf {
+        DIRS.config_dir().join(STEAM_FILE_NAME)
+    }
+
+    /// Reads the settings at `path`, or the default settings file if it exists.
+    ///
+    /// Returns `None` if there are no settings or they lack an API key.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>> {
+        let api = match path {
+            Some(path) => Self::from_path(path)?,
+            None if Self::path().exists() => Self::from_path(&Self::path())?,
+            None => return Ok(None),
+        };
+
+        Ok((!api.api_key.is_empty()).then_some(api))
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read Steam settings {}", path.display()))?;
+        let api = toml::from_str::<Self>(&content)
+            .with_context(|| format!("Invalid Steam settings {}", path.display()))?;
+
+        Ok(api)
+    }
+
+    /// Profiles of the given account ids, from the cache where it is fresh enough.
+    ///
+    /// Failed lookups are only logged, accounts the API doesn't know or that couldn't be looked
+    /// up are left out or keep their stale cache entry.
+    pub fn profiles(&self, steam_ids: &[u32]) -> HashMap<u32, Profile> {
+        self.profiles_cached_at(steam_ids, &DIRS.data_local_dir().join(CACHE_FILE_NAME))
+    }
+
+    fn profiles_cached_at(&self, steam_ids: &[u32], cache_path: &Path) -> HashMap<u32, Profile> {
+        let mut cache = match read_cache(cache_path) {
+            Ok(cache) => cache,
+            Err(err) => {
+                warn!("Ignoring Steam profile cache: {:#}", err);
+                HashMap::new()
+            }
+        };
+
+        let max_age = chrono::Duration::hours(self.cache_hours.into());
+        let now = Utc::now();
+        let mut stale = steam_ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                cache
+                    .get(id)
+                    .map_or(true, |profile| now - profile.fetched_at > max_age)
+            })
+            .collect::<Vec<_>>();
+        stale.sort_unstable();
+        stale.dedup();
+
+        if !stale.is_empty() {
+            let mut fetched = HashMap::new();
+            for ids in stale.chunks(MAX_IDS_PER_REQUEST) {
+                match self.fetch(ids) {
+                    Ok(profiles) => fetched.extend(profiles),
+                    Err(err) => {
+                        warn!("Failed to look up Steam profiles: {:#}", err);
+                        break;
+                    }
+                }
+            }
+
+            if !fetched.is_empty() {
+                cache.extend(fetched);
+                if let Err(err) = write_cache(cache_path, &cache) {
+                    warn!("Failed to write Steam profile cache: {:#}", err);
+                }
+            }
+        }
+
+        steam_ids
+            .iter()
+            .filter_map(|id| Some((*id, cache.get(id)?.clone())))
+            .collect()
+    }
+
+    fn fetch(&self, steam_ids: &[u32]) -> Result<HashMap<u32, Profile>> {
+        let url = format!(
+            "{}/ISteamUser/GetPlayerSummaries/v0002/",
+            self.base_url.trim_end_matches('/')
+        );
+        let ids = steam_ids
+            .iter()
+            .map(|id| (STEAM_ID64_BASE + u64::from(*id)).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        debug!("Looking up {} Steam profiles at {}", steam_ids.len(), url);
+
+        let response = ureq::get(&url)
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .query("key", &self.api_key)
+            .query("steamids", &ids)
+            .call()
+            .map_err(|err| anyhow!("Request to {} failed: {}", url, request_error(err)))?
+            .into_json::<SummariesResponse>()
+            .context("Unexpected response from the Steam Web API")?;
+
+        let fetched_at = Utc::now();
+        let profiles = response
+            .response
+            .players
+            .into_iter()
+            .filter_map(|player| {
+                let steam_id = player
+                    .steamid
+                    .parse::<u64>()
+                    .ok()?
+                    .checked_sub(STEAM_ID64_BASE)?;
+                let profile = Profile {
+                    persona_name: player.personaname,
+                    avatar_url: player.avatarfull,
+                    profile_url: player.profileurl,
+                    visibility: match player.communityvisibilitystate {
+                        3 => Visibility::Public,
+                        _ => Visibility::Private,
+                    },
+                    fetched_at,
+                };
+                Some((u32::try_from(steam_id).ok()?, profile))
+            })
+            .collect();
+
+        Ok(profiles)
+    }
+}
+
+
+impl Visibility {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Public => "public",
+        }
+    }
+}
+
+
+/// Describes a failed request without its URL, which has the API key in the query
+fn request_error(err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Status(status, response) => {
+            format!("status code {} {}", status, response.status_text())
+        }
+        ureq::Error::Transport(transport) => {
+            let mut description = transport.kind().to_string();
+            if let Some(message) = transport.message() {
+                description += &format!(": {}", message);
+            }
+            if let Some(source) = std::error::Error::source(&transport) {
+                description += &format!(": {}", source);
+            }
+            description
+        }
+    }
+}
+
+
+fn read_cache(path: &Path) -> Result<HashMap<u32, Profile>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let cache =
+        serde_json::from_str(&content).with_context(|| format!("Invalid {}", path.display()))?;
+
+    Ok(cache)
+}
+
+
+fn write_cache(path: &Path, cache: &HashMap<u32, Profile>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // several wayfarer processes may look up profiles at the same time
+    write_atomic(path, serde_json::to_string_pretty(cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use tiny_http::{Response, Server};
+
+    use super::*;
+
+    /// Serves a single player summary with `status`, and keeps the URLs it was asked for
+    fn mock_api(status: u16) -> (String, Arc<Mutex<Vec<String>>>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", server.server_addr());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = Arc::clone(&requests);
+        thread::spawn(move || {
+            for request in server.incoming_requests() {
+                seen.lock().unwrap().push(request.url().to_string());
+                let body = r#"{"response": {"players": [{
+                    "steamid": "76561199527978770",
+                    "personaname": "Sandsurfer",
+                    "avatarfull": "https://avatars.example/full.jpg",
+                    "profileurl": "https://steamcommunity.com/id/sandsurfer/",
+                    "communityvisibilitystate": 3
+                }]}}"#;
+                let _ = request.respond(Response::from_string(body).with_status_code(status));
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    fn api(base_url: &str) -> SteamApi {
+        SteamApi {
+            api_key: "KEY".to_string(),
+            base_url: base_url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn looks_up_and_caches_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(CACHE_FILE_NAME);
+        let (base_url, requests) = mock_api(200);
+
+        let profiles = api(&base_url).profiles_cached_at(&[1567713042, 339040052], &cache_path);
+        assert_eq!(profiles.len(), 1);
+        let profile = &profiles[&1567713042];
+        assert_eq!(profile.persona_name, "Sandsurfer");
+        assert_eq!(profile.visibility, Visibility::Public);
+
+        let url = requests.lock().unwrap()[0].clone();
+        assert!(url.starts_with("/ISteamUser/GetPlayerSummaries/v0002/?key=KEY&steamids="));
+        assert!(url.contains("76561198299305780%2C76561199527978770"));
+
+        // fresh profiles come from the cache, even without the API
+        let profiles = api("http://127.0.0.1:1").profiles_cached_at(&[1567713042], &cache_path);
+        assert_eq!(profiles[&1567713042].persona_name, "Sandsurfer");
+    }
+
+    #[test]
+    fn errors_leave_out_api_key() {
+        let (base_url, _) = mock_api(403);
+        let err = api(&base_url).fetch(&[1567713042]).unwrap_err();
+        assert!(format!("{:#}", err).ends_with("failed: status code 403 Forbidden"));
+
+        let err = api("http://127.0.0.1:1").fetch(&[1567713042]).unwrap_err();
+        assert!(format!("{:#}", err).contains("Connection Failed"));
+        assert!(!format!("{:#}", err).contains("KEY"));
+    }
+
+    #[test]
+    fn works_without_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join(CACHE_FILE_NAME);
+
+        let profiles = api("http://127.0.0.1:1").profiles_cached_at(&[1567713042], &cache_path);
+        assert!(profiles.is_empty());
+        assert!(!cache_path.exists());
+
+        let path = dir.path().join(STEAM_FILE_NAME);
+        fs::write(&path, "base_url = \"http://127.0.0.1:8080\"").unwrap();
+        assert!(SteamApi::load(Some(&path)).unwrap().is_none());
+
+        fs::write(&path, "api_key = \"KEY\"\ncache_hours = 1").unwrap();
+        let api = SteamApi::load(Some(&path)).unwrap().unwrap();
+        assert_eq!(api.base_url, DEFAULT_BASE_URL);
+        assert_eq!(api.cache_hours, 1);
+
+        fs::write(&path, "key = \"KEY\"").unwrap();
+        assert!(SteamApi::load(Some(&path)).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg(test)]
+
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use jrny_save::Savefile;
+use tempfile::TempDir;
+
+
+pub const TEST_FILE: &[u8] = include_bytes!("../../save/test.bin");
+
+
+/// The test savefile, read from memory so its path is empty
+pub fn test_savefile() -> Savefile {
+    Savefile::from_reader(Cursor::new(TEST_FILE)).unwrap()
+}
+
+
+/// A temporary directory with the test savefile written to `SAVE.BIN`, returns its path
+pub fn temp_savefile() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("SAVE.BIN");
+    fs::write(&path, TEST_FILE).unwrap();
+
+    (dir, path)
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{Duration, Local};
+use clap::Parser as ArgParser;
+
+use crate::backup::parse_age;
+use crate::history::{sessions, History, Session};
+use crate::locate::savefile_or_default;
+use crate::AppArgs;
+
+
+#[derive(Debug, Clone, ArgParser)]
+pub(crate) struct Args {
+    /// Savefile to summarize, defaults to the one last opened in the TUI
+    path: Option<PathBuf>,
+
+    /// Pause between records that starts a new session, e.g. 30m or 2h
+    #[arg(long, default_value = "1h", value_parser = parse_age)]
+    gap: Duration,
+}
+
+
+pub(crate) fn execute(_app_args: &AppArgs, sub_args: &Args) -> Result<()> {
+    let path = savefile_or_default(sub_args.path.as_deref())?;
+
+    let records = History::open()?.records_of(&path)?;
+
+    if records.is_empty() {
+        println!(
+            "Nothing recorded for {}, run `wayfarer record` or watch it in the TUI",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    println!("{}", path.display());
+
+    for session in sessions(&records, sub_args.gap) {
+        print_session(&session);
+    }
+
+    Ok(())
+}
+
+
+fn print_session(session: &Session) {
+    let (first, last) = (session.first(), session.last());
+
+    let progress = |name: &str, from: u64, to: u64| match to.saturating_sub(from) {
+        0 => format!("{} {:>3}      ", name, to),
+        gained => format!("{} {:>3} {:<5}", name, to, format!("(+{})", gained)),
+    };
+
+    let line = format!(
+        "  {} - {}  {}  {}  {}  {}",
+        session
+            .start()
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M"),
+        session.end().with_timezone(&Local).format("%H:%M"),
+        progress("journeys", first.journey_count, last.journey_count),
+        progress(
+            "glyphs",
+            first.glyphs_found.into(),
+            last.glyphs_found.into()
+        ),
+        progress(
+            "murals",
+            first.murals_found.into(),
+            last.murals_found.into()
+        ),
+        progress(
+            "companions",
+            first.total_companions_met.into(),
+            last.total_companions_met.into()
+        ),
+    );
+
+    println!("{}", line.trim_end());
+}
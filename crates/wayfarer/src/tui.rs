@@ -20,8 +20,11 @@ use crossterm::terminal::{
 };
 use ratatui::backend::CrosstermBackend;
 use tracing::{debug, info};
+use tui_input::Input;
 
 use self::state::{Mode, State};
+use crate::hooks::Hooks;
+use crate::steam::SteamApi;
 
 
 type Terminal = ratatui::Terminal<CrosstermBackend<Stdout>>;
@@ -32,6 +35,24 @@ pub struct Args {
     /// Overrides the last loaded file
     #[arg(long, short)]
     path: Option<PathBuf>,
+
+    /// Updates Steam Cloud's remotecache.vdf when saving
+    #[arg(long)]
+    steam_cloud: bool,
+
+    /// Stores a backup whenever the savefile is reloaded, e.g. by the file watcher
+    #[arg(long)]
+    backup_on_reload: bool,
+
+    /// Runs the hooks in this TOML file when a reload shows progress, instead of hooks.toml in
+    /// the config directory
+    #[arg(long)]
+    hooks: Option<PathBuf>,
+
+    /// Looks up companion profiles with the Steam Web API settings in this TOML file, instead of
+    /// steam.toml in the config directory
+    #[arg(long)]
+    steam_api: Option<PathBuf>,
 }
 
 
@@ -53,6 +74,22 @@ pub enum Message {
 
     LoadFile,
 
+    LocateFiles,
+
+    LoadPickedFile,
+
+    ListBackups,
+
+    RestoreBackup,
+
+    ListSlots,
+
+    LoadSlot,
+
+    ShowTimeline,
+
+    ShowRoster,
+
     #[cfg(feature = "watch")]
     ToggleFileWatch,
 
@@ -66,6 +103,12 @@ pub enum Message {
 
     PreviousEntryValue,
 
+    ToggleConsistentEdit,
+
+    ConfirmPendingEdit,
+
+    DiscardPendingEdit,
+
     ReloadFile,
 
     MoveSection(Direction),
@@ -77,7 +120,7 @@ pub enum Message {
 
 
 pub(crate) fn execute(args: &Args) -> Result<()> {
-    let state = match &args.path {
+    let mut state = match &args.path {
         Some(path) => {
             let mut state = State::default();
             state.set_savefile_from_path(path)?;
@@ -86,6 +129,12 @@ pub(crate) fn execute(args: &Args) -> Result<()> {
         None => State::load()?,
     };
 
+    state.steam_cloud = args.steam_cloud;
+    state.backup_on_reload = args.backup_on_reload;
+    state.hooks = Hooks::load(args.hooks.as_deref())?;
+    state.steam_api = SteamApi::load(args.steam_api.as_deref())?;
+    state.look_up_profiles();
+
     let mut terminal = setup()?;
 
     run(&mut terminal, state)?;
@@ -167,6 +216,13 @@ fn handle_message(
 
             msg_tx.send(Message::SetMode(Mode::Normal))?;
         }
+        Message::LocateFiles => state.locate_savefiles(),
+        Message::LoadPickedFile => {
+            if let Some(location) = state.picked_savefile() {
+                state.file_select = Input::new(location.path.display().to_string());
+                msg_tx.send(Message::LoadFile)?;
+            }
+        }
         #[cfg(feature = "watch")]
         Message::ToggleFileWatch => {
             if let Some(savefile) = &state.savefile {
@@ -181,11 +237,34 @@ fn handle_message(
 
                     info!("Starting file watcher on {}", savefile.path.display());
                     state.enable_file_watcher(callback);
+                    state.record_progress()?;
                 }
             }
         }
+        Message::ListBackups => state.list_backups()?,
+        Message::RestoreBackup => {
+            info!("Restoring backup");
+            state.restore_selected_backup()?;
+        }
+        Message::ListSlots => state.list_slots()?,
+        Message::LoadSlot => {
+            info!("Loading slot");
+            state.load_selected_slot()?;
+        }
+        Message::ShowTimeline => state.show_timeline()?,
+        Message::ShowRoster => state.show_roster()?,
         Message::ReloadFile => state.reload_active_savefile()?,
         Message::MoveSection(direction) => state.move_section(direction),
+        Message::MoveCur(direction) if state.mode == Mode::PickFile => {
+            state.move_in_picker(direction)
+        }
+        Message::MoveCur(direction) if state.mode == Mode::Backups => {
+            state.move_in_backups(direction)?
+        }
+        Message::MoveCur(direction) if state.mode == Mode::Slots => state.move_in_slots(direction),
+        Message::MoveCur(direction) if state.mode == Mode::Roster => {
+            state.move_in_roster(direction)
+        }
         Message::MoveCur(direction) => state.move_in_current_section(direction),
         Message::StartEditEntry => state.start_editing_entry(),
         Message::CommitEditEntry => {
@@ -204,6 +283,9 @@ fn handle_message(
                 state.show_error_message(err);
             }
         }
+        Message::ToggleConsistentEdit => state.toggle_consistent_edit(),
+        Message::ConfirmPendingEdit => state.confirm_pending_edit(),
+        Message::DiscardPendingEdit => state.discard_pending_edit(),
         Message::SaveFile => {
             info!("Saving file");
             if let Err(err) = state.save_edited_file() {
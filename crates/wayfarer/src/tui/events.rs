@@ -26,14 +26,12 @@ pub fn handle(event_queue: &mut mpsc::Sender<Message>, state: &mut State) -> Res
 
 
 fn handle_paste(value: String, state: &mut State) -> Result<()> {
-    match &state.mode {
-        Mode::SelectFile => {
-            debug!("Received pasted content: {:?}", value);
-            let combined = format!("{}{}", state.file_select.value(), value);
-            state.file_select = Input::new(combined);
-        }
-        _ => (),
+    if state.mode == Mode::SelectFile {
+        debug!("Received pasted content: {:?}", value);
+        let combined = format!("{}{}", state.file_select.value(), value);
+        state.file_select = Input::new(combined);
     }
+
     Ok(())
 }
 
@@ -46,6 +44,7 @@ fn handle_keyboard_input(
 ) -> Result<()> {
     match (key.code, &state.mode) {
         (KeyCode::Esc, Mode::Insert) => msg_tx.send(Message::CancelEditEntry)?,
+        (KeyCode::Esc, Mode::ConfirmEdit) => msg_tx.send(Message::DiscardPendingEdit)?,
         (KeyCode::Esc, Mode::SelectFile) => {
             if state.prompt_save {
                 msg_tx.send(Message::SetMode(Mode::Edit))?;
@@ -56,6 +55,18 @@ fn handle_keyboard_input(
         (KeyCode::Esc, _) => msg_tx.send(Message::SetMode(Mode::Normal))?,
         (KeyCode::Enter, Mode::Edit) => msg_tx.send(Message::StartEditEntry)?,
         (KeyCode::Enter, Mode::Insert) => msg_tx.send(Message::CommitEditEntry)?,
+        (KeyCode::Enter, Mode::ConfirmEdit) => msg_tx.send(Message::ConfirmPendingEdit)?,
+        (KeyCode::Enter, Mode::PickFile) => msg_tx.send(Message::LoadPickedFile)?,
+        (KeyCode::Down, Mode::PickFile) => msg_tx.send(Message::MoveCur(Direction::Down))?,
+        (KeyCode::Up, Mode::PickFile) => msg_tx.send(Message::MoveCur(Direction::Up))?,
+        (KeyCode::Enter, Mode::Backups) => msg_tx.send(Message::RestoreBackup)?,
+        (KeyCode::Down, Mode::Backups) => msg_tx.send(Message::MoveCur(Direction::Down))?,
+        (KeyCode::Up, Mode::Backups) => msg_tx.send(Message::MoveCur(Direction::Up))?,
+        (KeyCode::Enter, Mode::Slots) => msg_tx.send(Message::LoadSlot)?,
+        (KeyCode::Down, Mode::Slots) => msg_tx.send(Message::MoveCur(Direction::Down))?,
+        (KeyCode::Up, Mode::Slots) => msg_tx.send(Message::MoveCur(Direction::Up))?,
+        (KeyCode::Down, Mode::Roster) => msg_tx.send(Message::MoveCur(Direction::Down))?,
+        (KeyCode::Up, Mode::Roster) => msg_tx.send(Message::MoveCur(Direction::Up))?,
         (KeyCode::Enter, Mode::SelectFile) => {
             if state.prompt_save {
                 msg_tx.send(Message::SaveFile)?;
@@ -74,7 +85,12 @@ fn handle_keyboard_input(
                 state.file_select = Input::default();
                 msg_tx.send(Message::SetMode(Mode::SelectFile))?
             }
+            'f' => msg_tx.send(Message::LocateFiles)?,
             'r' => msg_tx.send(Message::ReloadFile)?,
+            'b' => msg_tx.send(Message::ListBackups)?,
+            's' => msg_tx.send(Message::ListSlots)?,
+            't' => msg_tx.send(Message::ShowTimeline)?,
+            'c' => msg_tx.send(Message::ShowRoster)?,
             #[cfg(feature = "watch")]
             'w' => msg_tx.send(Message::ToggleFileWatch)?,
             _ => (),
@@ -93,6 +109,8 @@ fn handle_keyboard_input(
             // next/previous value selection
             'n' => msg_tx.send(Message::NextEntryValue)?,
             'p' => msg_tx.send(Message::PreviousEntryValue)?,
+            // keep dependent fields consistent
+            'c' => msg_tx.send(Message::ToggleConsistentEdit)?,
             // open save prompt
             's' => {
                 state.prompt_save = true;
@@ -101,6 +119,21 @@ fn handle_keyboard_input(
             }
             _ => (),
         },
+        (KeyCode::Char(ch), Mode::PickFile) => match ch {
+            'j' => msg_tx.send(Message::MoveCur(Direction::Down))?,
+            'k' => msg_tx.send(Message::MoveCur(Direction::Up))?,
+            _ => (),
+        },
+        (KeyCode::Char(ch), Mode::Backups | Mode::Slots) => match ch {
+            'j' => msg_tx.send(Message::MoveCur(Direction::Down))?,
+            'k' => msg_tx.send(Message::MoveCur(Direction::Up))?,
+            _ => (),
+        },
+        (KeyCode::Char(ch), Mode::ConfirmEdit) => match ch {
+            'y' => msg_tx.send(Message::ConfirmPendingEdit)?,
+            'n' => msg_tx.send(Message::DiscardPendingEdit)?,
+            _ => (),
+        },
         (_, Mode::Insert) => {
             if let Some(input) = &mut state.edit_input {
                 input.handle_event(&Event::Key(key));
@@ -109,6 +142,10 @@ fn handle_keyboard_input(
         (_, Mode::SelectFile) => {
             state.file_select.handle_event(&Event::Key(key));
         }
+        (_, Mode::Roster) => {
+            state.roster_search.handle_event(&Event::Key(key));
+            state.roster_table.select(Some(0));
+        }
         _ => (),
     }
 
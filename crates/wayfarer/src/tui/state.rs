@@ -1,12 +1,15 @@
 use core::fmt;
-use std::fs::{self, create_dir_all, read_to_string, OpenOptions};
+use std::collections::HashMap;
+use std::fs::{self, create_dir_all, read_to_string};
 use std::io::Write;
 use std::os::unix::prelude::OsStrExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::{bail, Context, Result};
-use jrny_save::Savefile;
+use jrny_save::{Change, Edit, EditMode, RobeColor, Savefile, LEVEL_NAMES};
 use ratatui::widgets::TableState;
 use tracing::{debug, error};
 use tui_input::Input;
@@ -15,6 +18,14 @@ use super::view::info::glyphs::TABLE_RANGE as GLYPHS_TABLE_RANGE;
 use super::view::info::murals::TABLE_RANGE as MURALS_TABLE_RANGE;
 use super::view::info::stats::TABLE_RANGE as STATS_TABLE_RANGE;
 use super::Direction;
+use crate::backup::{BackupStore, Reason, Snapshot};
+use crate::edit::{write_data, write_savefile};
+use crate::history::{History, Record};
+use crate::hooks::Hooks;
+use crate::locate::{Location, Locator};
+use crate::roster::{self, Roster, RosterEntry};
+use crate::slot::{Slot, SlotStore};
+use crate::steam::{Profile, SteamApi};
 #[cfg(feature = "watch")]
 use crate::watcher::FileWatcher;
 use crate::DIRS;
@@ -30,11 +41,23 @@ pub enum Mode {
     Insert,
 
     SelectFile,
+
+    ConfirmEdit,
+
+    PickFile,
+
+    Backups,
+
+    Slots,
+
+    Timeline,
+
+    Roster,
 }
 
 impl Mode {
     pub fn is_editing(&self) -> bool {
-        self == &Self::Edit || self == &Self::Insert
+        self == &Self::Edit || self == &Self::Insert || self == &Self::ConfirmEdit
     }
 }
 
@@ -49,9 +72,17 @@ pub enum Section {
 }
 
 
+pub struct PendingEdit {
+    pub savefile: Savefile,
+    pub changes: Vec<Change>,
+}
+
+
 #[derive(Default)]
 pub struct State {
     pub savefile: Option<Savefile>,
+    /// Version last read from or written to disk, without unsaved edits
+    pub on_disk: Option<Savefile>,
     pub original_file: Option<Savefile>,
     pub active_section: Section,
     pub stats_table: TableState,
@@ -61,7 +92,26 @@ pub struct State {
     pub mode: Mode,
     pub prompt_save: bool,
     pub edit_input: Option<Input>,
+    pub edit_mode: EditMode,
+    pub pending_edit: Option<PendingEdit>,
     pub file_select: Input,
+    pub located: Vec<Location>,
+    pub located_table: TableState,
+    pub steam_cloud: bool,
+    pub backups: Vec<Snapshot>,
+    pub backups_table: TableState,
+    pub backup_preview: Vec<Change>,
+    pub backup_on_reload: bool,
+    pub slots: Vec<Slot>,
+    pub slots_table: TableState,
+    pub timeline: Vec<Record>,
+    pub roster: Roster,
+    pub roster_search: Input,
+    pub roster_table: TableState,
+    pub hooks: Hooks,
+    pub steam_api: Option<SteamApi>,
+    /// Steam profiles of the companions by account id, filled in the background
+    pub profiles: Arc<Mutex<HashMap<u32, Profile>>>,
     #[cfg(feature = "watch")]
     file_watcher: Option<FileWatcher>,
 }
@@ -74,10 +124,19 @@ impl State {
         let data_dir = DIRS.data_local_dir();
 
         if !data_dir.exists() {
-            create_dir_all(&data_dir)?;
+            create_dir_all(data_dir)?;
         }
 
-        let state = match load_last_active_savefile() {
+        let mut state = match load_last_active_savefile() {
+            Ok(None) => {
+                // help first time users by showing where savefiles were found
+                let mut state = Self::default();
+                state.locate_savefiles();
+                if state.mode != Mode::PickFile {
+                    state.clear_error_message();
+                }
+                state
+            }
             Ok(savefile) => Self {
                 savefile,
                 ..Default::default()
@@ -92,6 +151,8 @@ impl State {
             }
         };
 
+        state.update_roster();
+
         Ok(state)
     }
 
@@ -121,13 +182,44 @@ impl State {
         P: AsRef<Path>,
     {
         let savefile = Savefile::from_path(path)?;
+        self.on_disk = Some(savefile.clone());
         self.savefile = Some(savefile);
+        self.update_roster();
+        self.look_up_profiles();
 
         Ok(())
     }
 
+    pub fn locate_savefiles(&mut self) {
+        let locator = match Locator::new() {
+            Ok(locator) => locator,
+            Err(err) => {
+                self.show_error_message(err);
+                return;
+            }
+        };
+
+        self.located = locator.locate();
+
+        if self.located.is_empty() {
+            self.show_error_message("No savefiles found");
+        } else {
+            self.located_table.select(Some(0));
+            self.mode = Mode::PickFile;
+        }
+    }
+
+    pub fn picked_savefile(&self) -> Option<&Location> {
+        self.located.get(self.located_table.selected()?)
+    }
+
+    pub fn move_in_picker(&mut self, direction: Direction) {
+        let max = self.located.len().saturating_sub(1);
+        select_row_in_range(&mut self.located_table, direction, (0, max));
+    }
+
     pub fn set_selected_as_active_savefile(&mut self) -> Result<()> {
-        let savefile = Savefile::from_path(&self.file_select.value())?;
+        let savefile = Savefile::from_path(self.file_select.value())?;
 
         let state_path = DIRS.data_local_dir().join("active_savefile");
         let mut state_file = fs::OpenOptions::new()
@@ -139,7 +231,10 @@ impl State {
         let active_savefile = savefile.path.as_os_str().as_bytes();
 
         state_file.write_all(active_savefile)?;
+        self.on_disk = Some(savefile.clone());
         self.savefile = Some(savefile);
+        self.update_roster();
+        self.look_up_profiles();
 
         Ok(())
     }
@@ -160,105 +255,178 @@ impl State {
     pub fn commit_entry_edit(&mut self) -> Result<()> {
         debug!(section = ?self.active_section);
 
-        match self.active_section {
-            Section::General => self.edit_stats_section()?,
-            _ => (),
+        if self.active_section == Section::General {
+            let edit = self.stats_entry_edit()?;
+            self.apply_edit(edit)?;
         }
 
-        self.mode = Mode::Edit;
+        if self.mode == Mode::Insert {
+            self.mode = Mode::Edit;
+        }
 
         Ok(())
     }
 
-    fn edit_stats_section(&mut self) -> Result<()> {
-        let Some(savefile) = &mut self.savefile else {
-            bail!("No savefile loaded");
-        };
-
+    fn stats_entry_edit(&mut self) -> Result<Option<Edit>> {
         let input = self.edit_input.take().context("no edit input")?;
         let value = input.value();
 
-        match self.stats_table.selected().context("no selection")? {
-            0 => savefile.journey_count = value.parse()?,
-            1 => savefile.total_companions_met = value.parse()?,
-            2 => savefile.total_collected_symbols = value.parse()?,
-            3 => savefile.current_level.set_by_name(value)?,
-            4 => savefile.companions_met = value.parse()?,
-            5 => savefile.scarf_length.set_length(value.parse()?)?,
-            6 => savefile.symbol.set_by_id(value.parse()?)?,
-            7 => savefile.robe.set_color(value.parse()?),
-            8 => savefile.robe.set_tier(value.parse()?)?,
-            9 => {}
-            idx => debug!("unknown index {:?}", idx),
-        }
+        let edit = match self.stats_table.selected().context("no selection")? {
+            0 => Edit::JourneyCount(value.parse()?),
+            1 => Edit::TotalCompanionsMet(value.parse()?),
+            2 => Edit::TotalCollectedSymbols(value.parse()?),
+            3 => {
+                let id = LEVEL_NAMES
+                    .iter()
+                    .position(|&name| name == value)
+                    .context("Level name was not found")?;
+                Edit::CurrentLevel(id as u64)
+            }
+            4 => Edit::CompanionsMet(value.parse()?),
+            5 => Edit::ScarfLength(value.parse()?),
+            6 => Edit::Symbol(value.parse()?),
+            7 => Edit::RobeColor(value.parse()?),
+            8 => Edit::RobeTier(value.parse()?),
+            9 => return Ok(None),
+            idx => {
+                debug!("unknown index {:?}", idx);
+                return Ok(None);
+            }
+        };
 
-        Ok(())
+        Ok(Some(edit))
     }
 
     pub fn next_entry_value(&mut self) -> Result<()> {
-        match self.active_section {
-            Section::General => self.next_stats_entry_value()?,
-            _ => (),
+        if self.active_section == Section::General {
+            let edit = self.next_stats_entry_value()?;
+            self.apply_edit(edit)?;
         }
 
         Ok(())
     }
 
-    fn next_stats_entry_value(&mut self) -> Result<()> {
-        let Some(savefile) = &mut self.savefile else {
+    fn next_stats_entry_value(&self) -> Result<Option<Edit>> {
+        let Some(savefile) = &self.savefile else {
             bail!("No savefile loaded");
         };
 
-        match self.stats_table.selected().context("no selection")? {
-            0 => savefile.journey_count += 1,
-            1 => savefile.total_companions_met += 1,
-            2 => savefile.total_collected_symbols += 1,
-            3 => savefile.current_level = savefile.current_level.wrapping_next(),
-            4 => savefile.companions_met += 1,
-            5 => savefile.scarf_length.increase_length()?,
-            6 => savefile.symbol = savefile.symbol.wrapping_next(),
-            7 => savefile.robe.swap_colors(),
-            8 => savefile.robe.increase_tier(),
-            9 => {}
-            idx => debug!("unknown index {:?}", idx),
-        }
+        let edit = match self.stats_table.selected().context("no selection")? {
+            0 => Edit::JourneyCount(savefile.journey_count + 1),
+            1 => Edit::TotalCompanionsMet(savefile.total_companions_met + 1),
+            2 => Edit::TotalCollectedSymbols(savefile.total_collected_symbols + 1),
+            3 => Edit::CurrentLevel(*savefile.current_level.wrapping_next().as_ref()),
+            4 => Edit::CompanionsMet(savefile.companions_met + 1),
+            5 => {
+                let mut scarf = savefile.scarf_length.clone();
+                scarf.increase_length()?;
+                Edit::ScarfLength(*scarf.as_ref())
+            }
+            6 => Edit::Symbol(*savefile.symbol.wrapping_next().as_ref()),
+            7 => Edit::RobeColor(opposite_color(savefile.robe.color())),
+            8 => {
+                let mut robe = savefile.robe.clone();
+                robe.increase_tier();
+                Edit::RobeTier(robe.tier())
+            }
+            9 => return Ok(None),
+            idx => {
+                debug!("unknown index {:?}", idx);
+                return Ok(None);
+            }
+        };
 
-        Ok(())
+        Ok(Some(edit))
     }
 
     pub fn previous_entry_value(&mut self) -> Result<()> {
-        match self.active_section {
-            Section::General => self.previous_stats_entry_value()?,
-            _ => (),
+        if self.active_section == Section::General {
+            let edit = self.previous_stats_entry_value()?;
+            self.apply_edit(edit)?;
         }
 
         Ok(())
     }
 
-    fn previous_stats_entry_value(&mut self) -> Result<()> {
-        let Some(savefile) = &mut self.savefile else {
+    fn previous_stats_entry_value(&self) -> Result<Option<Edit>> {
+        let Some(savefile) = &self.savefile else {
             bail!("No savefile loaded");
         };
 
-        match self.stats_table.selected().context("no selection")? {
-            0 => savefile.journey_count = savefile.journey_count.saturating_sub(1),
-            1 => savefile.total_companions_met = savefile.total_companions_met.saturating_sub(1),
-            2 => {
-                savefile.total_collected_symbols =
-                    savefile.total_collected_symbols.saturating_sub(1)
+        let edit = match self.stats_table.selected().context("no selection")? {
+            0 => Edit::JourneyCount(savefile.journey_count.saturating_sub(1)),
+            1 => Edit::TotalCompanionsMet(savefile.total_companions_met.saturating_sub(1)),
+            2 => Edit::TotalCollectedSymbols(savefile.total_collected_symbols.saturating_sub(1)),
+            3 => Edit::CurrentLevel(*savefile.current_level.wrapping_previous().as_ref()),
+            4 => Edit::CompanionsMet(savefile.companions_met.saturating_sub(1)),
+            5 => {
+                let mut scarf = savefile.scarf_length.clone();
+                scarf.decrease_length()?;
+                Edit::ScarfLength(*scarf.as_ref())
             }
-            3 => savefile.current_level = savefile.current_level.wrapping_previous(),
-            4 => savefile.companions_met = savefile.companions_met.saturating_sub(1),
-            5 => savefile.scarf_length.decrease_length()?,
-            6 => savefile.symbol = savefile.symbol.wrapping_previous(),
-            7 => savefile.robe.swap_colors(),
-            8 => savefile.robe.decrease_tier(),
-            9 => {}
-            idx => debug!("unknown index {:?}", idx),
+            6 => Edit::Symbol(*savefile.symbol.wrapping_previous().as_ref()),
+            7 => Edit::RobeColor(opposite_color(savefile.robe.color())),
+            8 => {
+                let mut robe = savefile.robe.clone();
+                robe.decrease_tier();
+                Edit::RobeTier(robe.tier())
+            }
+            9 => return Ok(None),
+            idx => {
+                debug!("unknown index {:?}", idx);
+                return Ok(None);
+            }
+        };
+
+        Ok(Some(edit))
+    }
+
+    /// Applies an edit to the active savefile.
+    ///
+    /// If the edit changes more than the edited field itself, it is kept as pending edit until
+    /// it gets confirmed.
+    fn apply_edit(&mut self, edit: Option<Edit>) -> Result<()> {
+        let Some(edit) = edit else {
+            return Ok(());
+        };
+
+        let savefile = self.savefile.as_ref().context("No savefile loaded")?;
+
+        let (edited, changes) = savefile.preview(&edit, self.edit_mode)?;
+
+        if changes.len() > 1 {
+            self.pending_edit = Some(PendingEdit {
+                savefile: edited,
+                changes,
+            });
+            self.mode = Mode::ConfirmEdit;
+        } else {
+            self.savefile = Some(edited);
         }
+
         Ok(())
     }
 
+    pub fn confirm_pending_edit(&mut self) {
+        if let Some(pending) = self.pending_edit.take() {
+            self.savefile = Some(pending.savefile);
+        }
+
+        self.mode = Mode::Edit;
+    }
+
+    pub fn discard_pending_edit(&mut self) {
+        self.pending_edit = None;
+        self.mode = Mode::Edit;
+    }
+
+    pub fn toggle_consistent_edit(&mut self) {
+        self.edit_mode = match self.edit_mode {
+            EditMode::Plain => EditMode::Consistent,
+            EditMode::Consistent => EditMode::Plain,
+        };
+    }
+
     pub fn cancel_editing_entry(&mut self) {
         if self.mode == Mode::Insert {
             self.edit_input = None;
@@ -291,28 +459,225 @@ impl State {
         self.file_watcher = None;
     }
 
+    /// Adds the active savefile to the progress history if it changed
+    #[cfg(feature = "watch")]
+    pub fn record_progress(&self) -> Result<()> {
+        if let Some(savefile) = &self.savefile {
+            History::open()?.record(&savefile.path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the companions of the active savefile to the roster
+    fn update_roster(&mut self) {
+        let result = match &self.savefile {
+            Some(savefile) => roster::observe(savefile),
+            None => Ok(()),
+        };
+
+        if let Err(err) = result {
+            self.show_error_message(err);
+        }
+    }
+
+    /// Looks up the Steam profiles of the companions, unless the Steam Web API isn't set up
+    pub fn look_up_profiles(&self) {
+        let (Some(steam_api), Some(savefile)) = (&self.steam_api, &self.savefile) else {
+            return
+        };
+
+        let steam_api = steam_api.clone();
+        let steam_ids = savefile
+            .companions
+            .iter()
+            .map(|companion| companion.steam_id)
+            .collect::<Vec<_>>();
+        let profiles = Arc::clone(&self.profiles);
+
+        // the API can be slow or unreachable, the panel fills in when the lookup is done
+        thread::spawn(move || {
+            let found = steam_api.profiles(&steam_ids);
+            profiles.lock().unwrap().extend(found);
+        });
+    }
+
+    pub fn show_roster(&mut self) -> Result<()> {
+        self.roster = Roster::load()?;
+        self.roster_search = Input::default();
+        self.roster_table.select(Some(0));
+        self.mode = Mode::Roster;
+
+        Ok(())
+    }
+
+    /// Roster entries matching the search input
+    pub fn roster_matches(&self) -> Vec<&RosterEntry> {
+        self.roster.search(self.roster_search.value())
+    }
+
+    pub fn move_in_roster(&mut self, direction: Direction) {
+        let max = self.roster_matches().len().saturating_sub(1);
+        select_row_in_range(&mut self.roster_table, direction, (0, max));
+    }
+
+    pub fn show_timeline(&mut self) -> Result<()> {
+        let savefile = self.savefile.as_ref().context("no active savefile")?;
+
+        self.timeline = History::open()?.records_of(&savefile.path)?;
+
+        if self.timeline.is_empty() {
+            bail!("No progress recorded yet, watch the savefile with 'w' to record it");
+        }
+
+        self.mode = Mode::Timeline;
+
+        Ok(())
+    }
+
     pub fn reload_active_savefile(&mut self) -> Result<()> {
         if let Some(cur_savefile) = &self.savefile {
             debug!("Reloading file");
+            if self.backup_on_reload {
+                BackupStore::open()?.snapshot(&cur_savefile.path, Reason::Reload)?;
+            }
+            #[cfg(feature = "watch")]
+            if self.is_watching_file() {
+                self.record_progress()?;
+            }
             let new_savefile = Savefile::from_path(&cur_savefile.path)?;
+            // unsaved edits are not progress, only what changed on disk is
+            if let Some(on_disk) = &self.on_disk {
+                self.hooks.fire(on_disk, &new_savefile);
+            }
+            self.on_disk = Some(new_savefile.clone());
             self.savefile = Some(new_savefile);
+            self.update_roster();
+            self.look_up_profiles();
         }
 
         Ok(())
     }
 
     pub fn save_edited_file(&mut self) -> Result<()> {
-        let path = self.file_select.value();
+        let path = PathBuf::from(self.file_select.value());
+        let savefile = self.savefile.as_ref().context("no active savefile")?;
+
+        let store = BackupStore::open()?;
+        let warnings =
+            write_savefile(&store, &path, savefile, self.steam_cloud, Reason::Save)?.warnings;
+        if path == savefile.path {
+            self.on_disk = Some(savefile.clone());
+        }
+        if !warnings.is_empty() {
+            self.show_error_message(warnings.join("; "));
+        }
 
+        Ok(())
+    }
+
+    pub fn list_backups(&mut self) -> Result<()> {
         let savefile = self.savefile.as_ref().context("no active savefile")?;
 
-        let outfile = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(path)?;
+        self.backups = BackupStore::open()?.history(&savefile.path)?;
+
+        if self.backups.is_empty() {
+            bail!("No backups of {}", savefile.path.display());
+        }
+
+        self.backups_table.select(Some(0));
+        self.mode = Mode::Backups;
+        self.update_backup_preview()
+    }
+
+    pub fn selected_backup(&self) -> Option<&Snapshot> {
+        self.backups.get(self.backups_table.selected()?)
+    }
+
+    pub fn move_in_backups(&mut self, direction: Direction) -> Result<()> {
+        let max = self.backups.len().saturating_sub(1);
+        select_row_in_range(&mut self.backups_table, direction, (0, max));
+        self.update_backup_preview()
+    }
 
-        savefile.write(outfile)?;
+    /// Shows what restoring the selected backup would change in the current savefile
+    fn update_backup_preview(&mut self) -> Result<()> {
+        self.backup_preview.clear();
+
+        let (Some(savefile), Some(snapshot)) = (&self.savefile, self.selected_backup()) else {
+            return Ok(());
+        };
+
+        let backup = BackupStore::open()?.load(snapshot)?;
+        self.backup_preview = savefile.diff(&backup);
+
+        Ok(())
+    }
+
+    pub fn restore_selected_backup(&mut self) -> Result<()> {
+        let snapshot = self.selected_backup().context("no backup selected")?;
+
+        let store = BackupStore::open()?;
+        let data = store.read(snapshot)?;
+        let warnings = write_data(
+            &store,
+            &snapshot.path,
+            &data,
+            self.steam_cloud,
+            Reason::Restore,
+        )?
+        .warnings;
+
+        self.reload_active_savefile()?;
+        self.mode = Mode::Normal;
+
+        if !warnings.is_empty() {
+            self.show_error_message(warnings.join("; "));
+        }
+
+        Ok(())
+    }
+
+    pub fn list_slots(&mut self) -> Result<()> {
+        self.slots = SlotStore::open()?.list()?;
+
+        if self.slots.is_empty() {
+            bail!("No slots saved, use `wayfarer slot save` to add one");
+        }
+
+        self.slots_table.select(Some(0));
+        self.mode = Mode::Slots;
+
+        Ok(())
+    }
+
+    pub fn move_in_slots(&mut self, direction: Direction) {
+        let max = self.slots.len().saturating_sub(1);
+        select_row_in_range(&mut self.slots_table, direction, (0, max));
+    }
+
+    /// Replaces the active savefile with the selected slot
+    pub fn load_selected_slot(&mut self) -> Result<()> {
+        let savefile = self.savefile.as_ref().context("no active savefile")?;
+        let slot = self
+            .slots_table
+            .selected()
+            .and_then(|idx| self.slots.get(idx))
+            .context("no slot selected")?;
+
+        let (_, warnings) = SlotStore::open()?.load(
+            &slot.name,
+            &BackupStore::open()?,
+            &savefile.path,
+            self.steam_cloud,
+        )?;
+
+        self.reload_active_savefile()?;
+        self.mode = Mode::Normal;
+
+        if !warnings.is_empty() {
+            self.show_error_message(warnings.join("; "));
+        }
 
         Ok(())
     }
@@ -382,6 +747,14 @@ fn select_row_in_range(table: &mut TableState, direction: Direction, (min, max):
 }
 
 
+fn opposite_color(color: RobeColor) -> RobeColor {
+    match color {
+        RobeColor::Red => RobeColor::White,
+        RobeColor::White => RobeColor::Red,
+    }
+}
+
+
 fn load_last_active_savefile() -> Result<Option<Savefile>> {
     let state_path = DIRS.data_local_dir().join("active_savefile");
 
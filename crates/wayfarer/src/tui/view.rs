@@ -1,13 +1,19 @@
+pub mod backups;
+pub mod confirm_edit;
+pub mod file_picker;
 pub mod info;
+pub mod roster;
+pub mod slots;
 pub mod status_bar;
+pub mod timeline;
 
 
 use std::io::Stdout;
 
 use ratatui::backend::CrosstermBackend;
-use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
-use super::State;
+use super::{Mode, State};
 
 
 type Frame<'a> = ratatui::Frame<'a, CrosstermBackend<Stdout>>;
@@ -23,4 +29,41 @@ pub(super) fn render(state: &mut State, frame: &mut Frame) {
     info::render(state, frame, rows[0]);
 
     status_bar::render(state, frame, rows[1]);
+
+    match state.mode {
+        Mode::ConfirmEdit => confirm_edit::render(state, frame, rows[0]),
+        Mode::PickFile => file_picker::render(state, frame, rows[0]),
+        Mode::Backups => backups::render(state, frame, rows[0]),
+        Mode::Slots => slots::render(state, frame, rows[0]),
+        Mode::Timeline => timeline::render(state, frame, rows[0]),
+        Mode::Roster => roster::render(state, frame, rows[0]),
+        _ => (),
+    }
+}
+
+
+/// Area of the given size in the middle of `area`, used for popups
+pub(super) fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let height = height.min(area.height);
+    let width = width.min(area.width);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((area.height - height) / 2),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length((area.width - width) / 2),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ])
+        .split(rows[1]);
+
+    columns[1]
 }
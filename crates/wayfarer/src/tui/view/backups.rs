@@ -0,0 +1,90 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Row, Table};
+
+use crate::backup::SHORT_ID_LEN;
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &mut State, frame: &mut Frame, area: Rect) {
+    let rows = state.backups.len().max(state.backup_preview.len()) as u16;
+    let area = centered_rect(area, area.width.saturating_sub(8).min(140), rows + 4);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(52), Constraint::Min(0)])
+        .split(area);
+
+    frame.render_widget(Clear, area);
+
+    render_list(state, frame, columns[0]);
+
+    render_preview(state, frame, columns[1]);
+}
+
+
+fn render_list(state: &mut State, frame: &mut Frame, area: Rect) {
+    let block = Block::default()
+        .title("Backups")
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let rows = state.backups.iter().map(|snapshot| {
+        Row::new([
+            snapshot.created_local(),
+            snapshot.reason.to_string(),
+            snapshot.short_id().to_string(),
+        ])
+    });
+
+    let widths = [
+        Constraint::Length(19),
+        Constraint::Length(7),
+        Constraint::Length(SHORT_ID_LEN as u16),
+    ];
+
+    let table = Table::new(rows)
+        .header(Row::new(["Created", "Reason", "Id"]).style(Style::default().fg(Color::Blue)))
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(block);
+
+    frame.render_stateful_widget(table, area, &mut state.backups_table);
+}
+
+
+fn render_preview(state: &State, frame: &mut Frame, area: Rect) {
+    let title = if state.backup_preview.is_empty() {
+        "Restoring changes nothing"
+    } else {
+        "Restoring changes"
+    };
+
+    let block = Block::default()
+        .title(title)
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let rows = state.backup_preview.iter().map(|change| {
+        Row::new([
+            change.field.clone(),
+            change.old.clone(),
+            format!("→ {}", change.new),
+        ])
+    });
+
+    let table = Table::new(rows)
+        .header(Row::new(["Field", "Current", "Backup"]).style(Style::default().fg(Color::Blue)))
+        .widths(&[
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ])
+        .block(block);
+
+    frame.render_widget(table, area);
+}
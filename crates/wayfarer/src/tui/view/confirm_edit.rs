@@ -0,0 +1,42 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Row, Table};
+
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &State, frame: &mut Frame, area: Rect) {
+    let Some(pending) = &state.pending_edit else {
+        return
+    };
+
+    let height = pending.changes.len() as u16 + 4;
+    let area = centered_rect(area, 80, height);
+
+    let block = Block::default()
+        .title("This edit also changes")
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let rows = pending.changes.iter().map(|change| {
+        Row::new([
+            change.field.clone(),
+            change.old.clone(),
+            format!("→ {}", change.new),
+        ])
+    });
+
+    let table = Table::new(rows)
+        .header(Row::new(["Field", "Old", "New"]).style(Style::default().fg(Color::Blue)))
+        .widths(&[
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ])
+        .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
+}
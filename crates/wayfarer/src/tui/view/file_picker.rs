@@ -0,0 +1,57 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Row, Table};
+
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &mut State, frame: &mut Frame, area: Rect) {
+    let height = state.located.len() as u16 + 4;
+    let area = centered_rect(area, area.width.saturating_sub(8), height);
+
+    // borders, padding and the spacing between the four columns
+    let path_width = area.width.saturating_sub(2 + 4 + 6 + 16 + 11 + 28);
+
+    let block = Block::default()
+        .title("Found savefiles")
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let rows = state.located.iter().map(|location| {
+        Row::new([
+            location
+                .modified
+                .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+            location.source.to_string(),
+            location
+                .account
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            location.path.display().to_string(),
+        ])
+    });
+
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(11),
+        Constraint::Length(28),
+        Constraint::Length(path_width),
+    ];
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(["Modified", "Source", "Account", "Path"])
+                .style(Style::default().fg(Color::Blue)),
+        )
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(table, area, &mut state.located_table);
+}
@@ -25,14 +25,16 @@ fn render_no_active_file(frame: &mut Frame, area: Rect) {
         .padding(Padding::horizontal(2))
         .borders(Borders::ALL);
 
-    let info = Paragraph::new("No active file.\nPress 'o' to open a file, or 'q' to quit.")
-        .block(info_block);
+    let info = Paragraph::new(
+        "No active file.\nPress 'o' to open a file, 'f' to find savefiles, or 'q' to quit.",
+    )
+    .block(info_block);
 
     frame.render_widget(info, area);
 }
 
 
-fn render_info(state: &mut State, mut frame: &mut Frame, area: Rect) {
+fn render_info(state: &mut State, frame: &mut Frame, area: Rect) {
     let columns = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -52,8 +54,8 @@ fn render_info(state: &mut State, mut frame: &mut Frame, area: Rect) {
         .constraints([Constraint::Ratio(10, 10)])
         .split(columns[1]);
 
-    stats::render(state, &mut frame, left_column[0]);
-    glyphs::render(state, &mut frame, left_column[1]);
-    murals::render(state, &mut frame, left_column[2]);
-    companions::render(state, &mut frame, right_column[0]);
+    stats::render(state, frame, left_column[0]);
+    glyphs::render(state, frame, left_column[1]);
+    murals::render(state, frame, left_column[2]);
+    companions::render(state, frame, right_column[0]);
 }
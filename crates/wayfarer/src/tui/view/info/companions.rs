@@ -1,13 +1,18 @@
+use jrny_save::CompanionWithId;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::Style;
 use ratatui::widgets::{Block, Borders, Padding, Row, Table};
+use unicode_width::UnicodeWidthStr;
 
 use crate::tui::state::{Mode, Section};
 use crate::tui::view::Frame;
 use crate::tui::State;
 
 
-pub(super) fn render<'a>(state: &State, frame: &mut Frame, area: Rect) {
+const MIN_NAME_WIDTH: usize = 12;
+
+
+pub(super) fn render(state: &State, frame: &mut Frame, area: Rect) {
     let Some(savefile) = &state.savefile else {
         return
     };
@@ -36,26 +41,54 @@ pub(super) fn render<'a>(state: &State, frame: &mut Frame, area: Rect) {
         .borders(Borders::TOP)
         .title_alignment(Alignment::Center);
 
-    let current_companions = Table::new(
-        savefile
-            .current_companions()
-            .map(|companion| Row::new([companion.name.clone(), companion.steam_url()])),
-    )
-    .widths(&[Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-    .block(current_companions_block);
+    // measure the terminal columns the names take up, CJK and emoji are wider than one column
+    let name_width = savefile
+        .companions
+        .iter()
+        .map(|companion| companion.name.to_string().width())
+        .max()
+        .unwrap_or_default()
+        .max(MIN_NAME_WIDTH) as u16;
+
+    // the persona column only shows up when the Steam Web API is set up
+    let profiles = state.profiles.lock().unwrap();
+    let row = |companion: &CompanionWithId| {
+        let mut cells = vec![companion.name.to_string()];
+        if state.steam_api.is_some() {
+            let persona = profiles
+                .get(&companion.steam_id)
+                .map(|profile| format!("{} ({})", profile.persona_name, profile.visibility.name()))
+                .unwrap_or_default();
+            cells.push(persona);
+        }
+        cells.push(companion.steam_url());
+        Row::new(cells)
+    };
+
+    let widths = if state.steam_api.is_some() {
+        vec![
+            Constraint::Length(name_width),
+            Constraint::Length(name_width + MIN_NAME_WIDTH as u16),
+            Constraint::Min(0),
+        ]
+    } else {
+        vec![Constraint::Length(name_width), Constraint::Min(0)]
+    };
+
+    let current_companions = Table::new(savefile.current_companions().map(row))
+        .widths(&widths)
+        .column_spacing(2)
+        .block(current_companions_block);
 
     let past_companions_block = Block::default()
         .title("Past")
         .borders(Borders::TOP)
         .title_alignment(Alignment::Center);
 
-    let past_companions = Table::new(
-        savefile
-            .past_companions()
-            .map(|companion| Row::new([companion.name.clone(), companion.steam_url()])),
-    )
-    .widths(&[Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-    .block(past_companions_block);
+    let past_companions = Table::new(savefile.past_companions().map(row))
+        .widths(&widths)
+        .column_spacing(2)
+        .block(past_companions_block);
 
     frame.render_widget(companions_block, area);
     frame.render_widget(current_companions, layout[0]);
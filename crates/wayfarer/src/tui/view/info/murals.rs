@@ -11,7 +11,7 @@ use crate::tui::State;
 pub const TABLE_RANGE: (usize, usize) = (0, 6);
 
 
-pub(super) fn render<'a>(state: &mut State, frame: &mut Frame, area: Rect) {
+pub(super) fn render(state: &mut State, frame: &mut Frame, area: Rect) {
     const FOUND_SIGN: &str = "▾";
     const NOT_FOUND_SIGN: &str = "▿";
 
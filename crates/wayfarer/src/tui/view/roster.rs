@@ -0,0 +1,78 @@
+use chrono::Local;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Row, Table};
+
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &mut State, frame: &mut Frame, area: Rect) {
+    let area = centered_rect(area, 110, area.height.saturating_sub(4));
+
+    let entries = state.roster_matches();
+
+    let title = format!(
+        "Companions ({} of {})",
+        entries.len(),
+        state.roster.search("").len()
+    );
+
+    let block = Block::default()
+        .title(title)
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let format_time = |time: chrono::DateTime<chrono::Utc>| {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    let rows = entries
+        .iter()
+        .map(|entry| {
+            Row::new([
+                entry.name.clone(),
+                entry.steam_id_v3(),
+                entry
+                    .symbol
+                    .map(|symbol| symbol.to_string())
+                    .unwrap_or_default(),
+                entry.times_seen.to_string(),
+                format_time(entry.first_seen),
+                format_time(entry.last_seen),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let widths = [
+        Constraint::Length(24),
+        Constraint::Length(16),
+        Constraint::Length(6),
+        Constraint::Length(4),
+        Constraint::Length(16),
+        Constraint::Length(16),
+    ];
+
+    let table = Table::new(rows)
+        .header(
+            Row::new([
+                "Name",
+                "Steam ID",
+                "Symbol",
+                "Seen",
+                "First seen",
+                "Last seen",
+            ])
+            .style(Style::default().fg(Color::Blue)),
+        )
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(table, area, &mut state.roster_table);
+}
@@ -0,0 +1,66 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Padding, Row, Table};
+
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &mut State, frame: &mut Frame, area: Rect) {
+    let height = state.slots.len() as u16 + 4;
+    let area = centered_rect(area, 100, height);
+
+    // borders, padding and the spacing between the five columns
+    let name_width = area.width.saturating_sub(2 + 4 + 8 + 10 + 16 + 10 + 16);
+
+    let block = Block::default()
+        .title("Slots")
+        .padding(Padding::horizontal(2))
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let rows = state.slots.iter().map(|slot| {
+        let modified = slot
+            .modified
+            .map(|modified| modified.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        match &slot.summary {
+            Some(summary) => Row::new([
+                slot.name.clone(),
+                summary.robe.clone(),
+                summary.level.clone(),
+                format!("{}%", summary.completion),
+                modified,
+            ]),
+            None => Row::new([
+                slot.name.clone(),
+                "unreadable".to_string(),
+                String::new(),
+                String::new(),
+                modified,
+            ]),
+        }
+    });
+
+    let widths = [
+        Constraint::Length(name_width),
+        Constraint::Length(10),
+        Constraint::Length(16),
+        Constraint::Length(10),
+        Constraint::Length(16),
+    ];
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(["Name", "Robe", "Level", "Completion", "Modified"])
+                .style(Style::default().fg(Color::Blue)),
+        )
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(table, area, &mut state.slots_table);
+}
@@ -1,3 +1,4 @@
+use jrny_save::EditMode;
 use ratatui::layout::Rect;
 use ratatui::style::Style;
 use ratatui::widgets::{Block, Padding, Paragraph};
@@ -10,7 +11,7 @@ pub fn render(state: &State, frame: &mut Frame, area: Rect) {
     let status_block = Block::default().padding(Padding::horizontal(2));
 
     match &state.error_msg {
-        Some((_, msg)) => render_error_message(&msg, frame, status_block, area),
+        Some((_, msg)) => render_error_message(msg, frame, status_block, area),
         None => render_status(state, frame, status_block, area),
     }
 }
@@ -22,11 +23,48 @@ fn render_error_message(msg: &str, frame: &mut Frame, block: Block, area: Rect)
     frame.render_widget(error_msg, area);
 }
 
-pub fn render_status(state: &State, mut frame: &mut Frame, block: Block, area: Rect) {
+pub fn render_status(state: &State, frame: &mut Frame, block: Block, area: Rect) {
     match &state.mode {
+        Mode::ConfirmEdit => {
+            let text = "Apply edit with dependent changes? (Enter/y to apply, Esc/n to discard)";
+            let status = Paragraph::new(text).block(block);
+            frame.render_widget(status, area);
+        }
+
+        Mode::Backups => {
+            let text = "Restore this backup? (Enter to restore, Esc to cancel)";
+            let status = Paragraph::new(text).block(block);
+            frame.render_widget(status, area);
+        }
+
+        Mode::Slots => {
+            let text = "Replace the savefile with this slot? (Enter to load, Esc to cancel)";
+            let status = Paragraph::new(text).block(block);
+            frame.render_widget(status, area);
+        }
+
+        Mode::Timeline => {
+            let text = "Progress over time (Esc to close)";
+            let status = Paragraph::new(text).block(block);
+            frame.render_widget(status, area);
+        }
+
+        Mode::Roster => render_roster_search(state, frame, block, area),
+
+        Mode::PickFile => {
+            let text = "Open a found savefile? (Enter to open, Esc to cancel)";
+            let status = Paragraph::new(text).block(block);
+            frame.render_widget(status, area);
+        }
+
         Mode::Edit | Mode::Insert => {
             if let Some(savefile) = &state.savefile {
-                let text = format!("Editing file: {}", savefile.path.display());
+                let text = match state.edit_mode {
+                    EditMode::Consistent => {
+                        format!("Editing file (consistent): {}", savefile.path.display())
+                    }
+                    EditMode::Plain => format!("Editing file: {}", savefile.path.display()),
+                };
                 let status = Paragraph::new(text).block(block);
                 frame.render_widget(status, area);
             }
@@ -41,7 +79,7 @@ pub fn render_status(state: &State, mut frame: &mut Frame, block: Block, area: R
             }
         }
 
-        Mode::SelectFile => render_file_select(&state, &mut frame, block, area),
+        Mode::SelectFile => render_file_select(state, frame, block, area),
 
         _ => {
             if let Some(savefile) = &state.savefile {
@@ -72,3 +110,18 @@ fn render_file_select(state: &State, frame: &mut Frame, block: Block, area: Rect
         area.y,
     );
 }
+
+fn render_roster_search(state: &State, frame: &mut Frame, block: Block, area: Rect) {
+    const PADDING: usize = 2;
+    const PROMPT: &str = "Search companions:";
+
+    let scroll = state.roster_search.visual_scroll(area.width as usize);
+    let input = Paragraph::new(format!("{} {}", PROMPT, state.roster_search.value()))
+        .scroll((0, scroll as u16))
+        .block(block);
+    frame.render_widget(input, area);
+    frame.set_cursor(
+        area.x + (state.roster_search.visual_cursor() + PROMPT.len() + 1 + PADDING) as u16,
+        area.y,
+    );
+}
@@ -0,0 +1,86 @@
+use chrono::Local;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Span;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType};
+
+use crate::history::Record;
+use crate::tui::view::{centered_rect, Frame};
+use crate::tui::State;
+
+
+pub(super) fn render(state: &State, frame: &mut Frame, area: Rect) {
+    let (Some(first), Some(last)) = (state.timeline.first(), state.timeline.last()) else {
+        return
+    };
+
+    let area = centered_rect(area, area.width.saturating_sub(8), 24);
+
+    let points = |value: fn(&Record) -> u64| {
+        state
+            .timeline
+            .iter()
+            .map(|record| (record.recorded.timestamp() as f64, value(record) as f64))
+            .collect::<Vec<_>>()
+    };
+
+    let journeys = points(|record| record.journey_count);
+    let glyphs = points(|record| record.glyphs_found.into());
+    let murals = points(|record| record.murals_found.into());
+    let companions = points(|record| record.total_companions_met.into());
+
+    let x_bounds = [
+        first.recorded.timestamp() as f64,
+        (last.recorded.timestamp() as f64).max(first.recorded.timestamp() as f64 + 1.0),
+    ];
+
+    let y_max = [&journeys, &glyphs, &murals, &companions]
+        .iter()
+        .flat_map(|points| points.iter().map(|(_, y)| *y))
+        .fold(1.0, f64::max);
+
+    let dataset = |name, color, data| {
+        Dataset::default()
+            .name(name)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(data)
+    };
+
+    let datasets = vec![
+        dataset("Journeys", Color::Red, &journeys),
+        dataset("Glyphs", Color::Yellow, &glyphs),
+        dataset("Murals", Color::Cyan, &murals),
+        dataset("Companions", Color::Green, &companions),
+    ];
+
+    let format_time = |record: &Record| {
+        record
+            .recorded
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    let block = Block::default()
+        .title("Timeline")
+        .border_style(Style::default().fg(Color::Blue))
+        .borders(Borders::ALL);
+
+    let chart = Chart::new(datasets)
+        .block(block)
+        .x_axis(Axis::default().bounds(x_bounds).labels(vec![
+            Span::raw(format_time(first)),
+            Span::raw(format_time(last)),
+        ]))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, y_max])
+                .labels(vec![Span::raw("0"), Span::raw(format!("{}", y_max))]),
+        );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(chart, area);
+}
@@ -0,0 +1,285 @@
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+use std::{fmt, fs};
+
+use anyhow::{bail, Context, Result};
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    String(String),
+    Object(Vec<(String, Value)>),
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+
+impl Value {
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        parse(&content).with_context(|| format!("Invalid VDF file {}", path.display()))
+    }
+
+    /// Looks up a key of an object, keys are case-insensitive
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Entries of an object, empty for strings
+    pub fn entries(&self) -> &[(String, Value)] {
+        match self {
+            Self::Object(entries) => entries,
+            Self::String(_) => &[],
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Self::Object(entries) => entries
+                .iter_mut()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            Self::String(_) => None,
+        }
+    }
+
+    /// Replaces the value of a key in an object or appends it if missing
+    pub fn set(&mut self, key: &str, value: Value) {
+        if let Some(existing) = self.get_mut(key) {
+            *existing = value;
+        } else if let Self::Object(entries) = self {
+            entries.push((key.to_string(), value));
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            Self::Object(_) => None,
+        }
+    }
+
+    fn write_entries(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "\t".repeat(depth);
+
+        for (key, value) in self.entries() {
+            match value {
+                Self::String(value) => {
+                    writeln!(f, "{}\"{}\"\t\t\"{}\"", indent, escape(key), escape(value))?
+                }
+                Self::Object(_) => {
+                    writeln!(f, "{}\"{}\"", indent, escape(key))?;
+                    writeln!(f, "{}{{", indent)?;
+                    value.write_entries(f, depth + 1)?;
+                    writeln!(f, "{}}}", indent)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the entries of an object in the layout Steam uses
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "\"{}\"", escape(value)),
+            Self::Object(_) => self.write_entries(f, 0),
+        }
+    }
+}
+
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+/// Parses the text KeyValues format of Steam's `.vdf` files into an object holding the
+/// top level entries
+pub(crate) fn parse(input: &str) -> Result<Value> {
+    let mut tokens = Tokenizer {
+        chars: input.chars().peekable(),
+    };
+
+    let entries = parse_entries(&mut tokens, false)?;
+
+    Ok(Value::Object(entries))
+}
+
+
+fn parse_entries(tokens: &mut Tokenizer, nested: bool) -> Result<Vec<(String, Value)>> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next_token()? {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            None => bail!("Unexpected end of file, missing closing brace"),
+            Some(token) => bail!("Expected key, found {:?}", token),
+        };
+
+        let value = match tokens.next_token()? {
+            Some(Token::String(value)) => Value::String(value),
+            Some(Token::Open) => Value::Object(parse_entries(tokens, true)?),
+            _ => bail!("Missing value for key {}", key),
+        };
+
+        entries.push((key, value));
+    }
+}
+
+
+struct Tokenizer<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Tokenizer<'_> {
+    fn next_token(&mut self) -> Result<Option<Token>> {
+        loop {
+            let Some(ch) = self.chars.next() else {
+                return Ok(None);
+            };
+
+            match ch {
+                '{' => return Ok(Some(Token::Open)),
+                '}' => return Ok(Some(Token::Close)),
+                '"' => return self.quoted().map(|value| Some(Token::String(value))),
+                '/' if self.chars.peek() == Some(&'/') => self.skip_line(),
+                // platform conditionals like [$WIN32] are ignored
+                '[' => self.skip_until(']'),
+                ch if ch.is_whitespace() => (),
+                ch => return Ok(Some(Token::String(self.unquoted(ch)))),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(ch) => value.push(ch),
+                    None => break,
+                },
+                Some(ch) => value.push(ch),
+                None => break,
+            }
+        }
+
+        bail!("Unterminated string")
+    }
+
+    fn unquoted(&mut self, first: char) -> String {
+        let mut value = String::from(first);
+
+        while let Some(&ch) = self.chars.peek() {
+            if ch.is_whitespace() || matches!(ch, '"' | '{' | '}') {
+                break;
+            }
+            value.push(ch);
+            self.chars.next();
+        }
+
+        value
+    }
+
+    fn skip_line(&mut self) {
+        self.skip_until('\n');
+    }
+
+    fn skip_until(&mut self, end: char) {
+        for ch in self.chars.by_ref() {
+            if ch == end {
+                break;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn library_folders() {
+        let input = r#"
+            "libraryfolders"
+            {
+                // comments are skipped
+                "0"
+                {
+                    "path"      "/home/user/.local/share/Steam"
+                    "apps"      { "638230"  "1234" }
+                }
+                "1"     "D:\\Steam Library"
+                "2"     { "path" "/mnt/games" } [$LINUX]
+            }
+        "#;
+
+        let value = parse(input).unwrap();
+        let folders = value.get("LibraryFolders").unwrap();
+
+        assert_eq!(folders.entries().len(), 3);
+        assert_eq!(
+            folders.get("0").unwrap().get("path").unwrap().as_str(),
+            Some("/home/user/.local/share/Steam")
+        );
+        assert_eq!(
+            folders.get("0").unwrap().get("apps").unwrap().get("638230"),
+            Some(&Value::String("1234".to_string()))
+        );
+        assert_eq!(
+            folders.get("1").unwrap().as_str(),
+            Some("D:\\Steam Library")
+        );
+        assert_eq!(
+            folders.get("2").unwrap().get("path").unwrap().as_str(),
+            Some("/mnt/games")
+        );
+    }
+
+    #[test]
+    fn write_roundtrip() {
+        let mut value = parse(r#""app" { "file" { "size" "1" } "quote" "a \"b\"" }"#).unwrap();
+
+        let file = value.get_mut("app").unwrap().get_mut("file").unwrap();
+        file.set("size", Value::String("2".to_string()));
+        file.set("sha", Value::String("abc".to_string()));
+
+        let written = value.to_string();
+        assert!(written.starts_with("\"app\"\n{\n\t\"file\"\n\t{\n\t\t\"size\"\t\t\"2\"\n"));
+        assert_eq!(parse(&written).unwrap(), value);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(parse(r#""key" { "value" "#).is_err());
+        assert!(parse(r#""key""#).is_err());
+        assert!(parse(r#""key" "unterminated"#).is_err());
+        assert!(parse("}").is_err());
+    }
+}
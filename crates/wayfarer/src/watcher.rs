@@ -2,10 +2,10 @@
 
 use std::path::PathBuf;
 use std::sync::mpsc;
-use std::thread::{self, spawn};
+use std::thread::spawn;
 use std::time::Duration;
 
-use notify::event::{AccessKind, AccessMode};
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
 use notify::{Config as NotifyConfig, EventKind, RecommendedWatcher, Watcher};
 
 
@@ -17,37 +17,48 @@ impl FileWatcher {
     pub fn new<P, F>(path: P, callback: F) -> Self
     where
         P: Into<PathBuf>,
-        F: Fn() -> () + Send + 'static,
+        F: Fn() + Send + 'static,
     {
         let (exit_signal, exit) = mpsc::sync_channel(0);
         let (ev_tx, ev_rx) = mpsc::channel();
 
         let path = path.into();
 
+        // the directory is watched, as a file replaced by renaming another one over it has a new
+        // inode that a watch on the file itself would miss
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_owned(),
+            _ => PathBuf::from("."),
+        };
+
         spawn(move || {
             let mut watcher = RecommendedWatcher::new(ev_tx, NotifyConfig::default()).unwrap();
             watcher
-                .watch(path.as_ref(), notify::RecursiveMode::NonRecursive)
+                .watch(&dir, notify::RecursiveMode::NonRecursive)
                 .unwrap();
 
             let written_and_closed = EventKind::Access(AccessKind::Close(AccessMode::Write));
+            let renamed_to = EventKind::Modify(ModifyKind::Name(RenameMode::To));
+
+            let is_watched = |event_path: &PathBuf| event_path.file_name() == path.file_name();
 
+            // the directory reports several events per write, so they are handled as they come
+            // instead of one per check for the exit signal
             loop {
-                match ev_rx.try_recv() {
+                match ev_rx.recv_timeout(Duration::from_millis(500)) {
                     Ok(Ok(event)) => {
-                        if event.kind == written_and_closed {
+                        let replaced = event.kind == written_and_closed || event.kind == renamed_to;
+                        if replaced && event.paths.iter().any(is_watched) {
                             callback();
                         }
                     }
-                    Err(mpsc::TryRecvError::Empty) => (),
+                    Err(mpsc::RecvTimeoutError::Timeout) => (),
                     Err(_) | Ok(Err(_)) => break,
                 }
 
                 if exit.try_recv().is_ok() {
                     break;
                 }
-
-                thread::sleep(Duration::from_millis(500));
             }
         });
 